@@ -31,7 +31,7 @@ async fn main() -> Result<()> {
 
             println!("\nTop 5 Languages by Line Count:");
             let mut language_stats = project_analysis.get_language_statistics();
-            language_stats.sort_by(|a, b| b.total_lines.cmp(&a.total_lines));
+            language_stats.sort_by_key(|s| std::cmp::Reverse(s.total_lines));
 
             for (i, stats) in language_stats.iter().take(5).enumerate() {
                 println!(
@@ -15,7 +15,8 @@ SUPPORTED PLATFORMS:
   • SourceForge (sourceforge.net)
   • Gitea instances
   • Azure DevOps
-  • Direct archive URLs (tar.gz, tgz, zip)
+  • Direct archive URLs (tar.gz, tar.bz2, tar.xz, tar.zst, tar, zip)
+  • Local directories and archive files
 
 URL FORMATS:
   user/repo                           # GitHub repo (default branch)
@@ -24,6 +25,10 @@ URL FORMATS:
   https://github.com/user/repo        # Full GitHub URL
   https://gitlab.com/user/repo        # GitLab URL
   https://example.com/archive.tar.gz  # Direct archive URL
+  ./path/to/checkout                  # Local directory
+  file:///tmp/archive.tar.gz          # Local archive
+  -                                   # Archive piped to stdin
+  --git-dir repo.git --rev v2.3.0     # Revision of a local repository
 
 EXAMPLES:
   bradar microsoft/vscode
@@ -32,9 +37,17 @@ EXAMPLES:
   bradar --format json --detailed user/repo
   bradar --token ghp_xxx --include-tests private/repo
   bradar --aggressive-filter --max-file-size 2048 large/repo
+  git archive HEAD | bradar --name my-app -
 ")]
 #[command(arg_required_else_help = true)]
 #[command(disable_version_flag = true)]
+#[command(arg(
+    clap::Arg::new("version")
+        .short('v')
+        .long("version")
+        .action(clap::ArgAction::Version)
+        .help("Current version information")
+))]
 #[command(group(
     ArgGroup::new("auth")
         .args(&["token"])
@@ -47,12 +60,42 @@ EXAMPLES:
 ))]
 #[non_exhaustive]
 pub struct Cli {
-    #[arg(help = "Repository URL to analyze (user/repo, user/repo@branch, or full URL)")]
+    #[arg(
+        help = "Repository URL to analyze (user/repo, user/repo@branch, user/repo@branch:path, full URL, local path, or - for stdin)"
+    )]
     pub url: Option<String>,
 
-    // Version
-    #[arg(short = 'v', long = "version", action = clap::ArgAction::Version, help = "Current version information")]
-    version: (),
+    // Input Options
+    #[arg(
+        long = "stdin",
+        help = "Read the archive to analyze from standard input (same as `-`)",
+        conflicts_with = "url"
+    )]
+    pub stdin: bool,
+
+    #[arg(
+        long = "git-dir",
+        help = "Analyze a local git repository straight from its object database",
+        value_name = "DIR",
+        conflicts_with_all = ["url", "stdin"]
+    )]
+    pub git_dir: Option<String>,
+
+    #[arg(
+        long = "rev",
+        help = "Revision to analyze with --git-dir (commit, branch, tag, HEAD~N) [default: HEAD]",
+        value_name = "REV",
+        requires = "git_dir",
+        conflicts_with_all = ["url", "stdin"]
+    )]
+    pub rev: Option<String>,
+
+    #[arg(
+        long = "name",
+        help = "Project name to report instead of the one derived from the source",
+        value_name = "NAME"
+    )]
+    pub name: Option<String>,
 
     // Output Options
     #[arg(
@@ -67,6 +110,14 @@ pub struct Cli {
     #[arg(long = "detailed", help = "Show detailed file-by-file statistics")]
     pub detailed: bool,
 
+    #[arg(
+        long = "dir-depth",
+        help = "Directory levels to roll up in the directory tree (0 = off)",
+        default_value = "2",
+        value_name = "DEPTH"
+    )]
+    pub dir_depth: usize,
+
     #[arg(
         short = 'q',
         long = "quiet",
@@ -138,19 +189,43 @@ pub struct Cli {
     #[arg(long = "include-hidden", help = "Include hidden files and directories")]
     pub include_hidden: bool,
 
+    #[arg(
+        long = "keep-archive-root",
+        help = "Keep the archive's top-level directory in reported paths"
+    )]
+    pub keep_archive_root: bool,
+
+    #[arg(
+        long = "explain-filter",
+        help = "List every archive entry with the filter decision that applied to it"
+    )]
+    pub explain_filter: bool,
+
+    #[arg(
+        long = "respect-gitignore",
+        help = "Skip files excluded by .gitignore files inside the archive"
+    )]
+    pub respect_gitignore: bool,
+
+    #[arg(
+        long = "no-bradarignore",
+        help = "Ignore the .bradarignore file at the archive root"
+    )]
+    pub no_bradarignore: bool,
+
     #[arg(
         long = "exclude-pattern",
-        help = "Exclude files matching this pattern (glob)",
+        help = "Exclude files matching this glob pattern (repeatable)",
         value_name = "PATTERN"
     )]
-    pub exclude_pattern: Option<String>,
+    pub exclude_pattern: Vec<String>,
 
     #[arg(
         long = "include-pattern",
-        help = "Only include files matching this pattern (glob)",
+        help = "Only include files matching this glob pattern (repeatable)",
         value_name = "PATTERN"
     )]
-    pub include_pattern: Option<String>,
+    pub include_pattern: Vec<String>,
 
     #[arg(
         long = "min-file-size",
@@ -244,8 +319,9 @@ pub struct Cli {
     pub experimental_streaming: bool,
 }
 
-#[derive(Clone, ValueEnum)]
+#[derive(Clone, Default, ValueEnum)]
 pub enum OutputFormat {
+    #[default]
     #[value(name = "table", help = "Human-readable table format")]
     Table,
     #[value(name = "json", help = "JSON format")]
@@ -259,9 +335,3 @@ pub enum OutputFormat {
     #[value(name = "toml", help = "TOML format")]
     Toml,
 }
-
-impl Default for OutputFormat {
-    fn default() -> Self {
-        Self::Table
-    }
-}
@@ -19,6 +19,18 @@ use std::time::Instant;
 #[cfg(feature = "cli")]
 pub use args::{Cli, OutputFormat};
 
+/// Source argument that reads the archive from standard input.
+#[cfg(feature = "cli")]
+const STDIN: &str = "-";
+
+/// Where the project to analyze is read from.
+#[cfg(feature = "cli")]
+enum Source<'a> {
+    Url(&'a str),
+    Stdin,
+    Git { dir: &'a str, rev: &'a str },
+}
+
 #[cfg(feature = "cli")]
 pub async fn run() -> Result<()> {
     let cli = args::Cli::parse();
@@ -32,13 +44,19 @@ pub async fn run() -> Result<()> {
         }
     }
 
-    match &cli.url {
-        Some(url) => analyze_remote_archive(url, &cli).await,
-        None => {
-            url_parser::show_usage_examples();
-            std::process::exit(1);
-        }
-    }
+    let source = if let Some(dir) = &cli.git_dir {
+        let rev = cli.rev.as_deref().unwrap_or("HEAD");
+        Source::Git { dir, rev }
+    } else if cli.stdin || cli.url.as_deref() == Some(STDIN) {
+        Source::Stdin
+    } else if let Some(url) = &cli.url {
+        Source::Url(url)
+    } else {
+        url_parser::show_usage_examples();
+        std::process::exit(1);
+    };
+
+    analyze_source(&source, &cli).await
 }
 
 #[cfg(feature = "cli")]
@@ -72,11 +90,15 @@ fn init_logging(cli: &Cli) -> Result<()> {
 }
 
 #[cfg(feature = "cli")]
-async fn analyze_remote_archive(url: &str, cli: &Cli) -> Result<()> {
+async fn analyze_source(source: &Source<'_>, cli: &Cli) -> Result<()> {
     let should_show_progress =
         !cli.no_progress && matches!(cli.format, OutputFormat::Table) && !cli.quiet;
 
-    let processed_url = url_parser::expand_url(url);
+    let processed_url = match source {
+        Source::Url(url) => url_parser::expand_url(url),
+        Source::Stdin => "standard input".to_string(),
+        Source::Git { dir, rev } => format!("{} at {}", dir, rev),
+    };
 
     if should_show_progress && !cli.quiet {
         println!("Analyzing: {}", processed_url);
@@ -95,6 +117,7 @@ async fn analyze_remote_archive(url: &str, cli: &Cli) -> Result<()> {
 
     analyzer.set_timeout(cli.timeout);
     analyzer.set_allow_insecure(cli.allow_insecure);
+    analyzer.set_directory_depth(cli.dir_depth);
 
     if let Some(pb) = progress_bar.clone() {
         analyzer.set_progress_hook(progress::ProgressBarHook::new(pb));
@@ -102,7 +125,15 @@ async fn analyze_remote_archive(url: &str, cli: &Cli) -> Result<()> {
 
     configure_analyzer_filters(&mut analyzer, cli)?;
 
-    let project_analysis = analyzer.analyze_url(&processed_url).await?;
+    let mut project_analysis = match source {
+        Source::Url(url) => analyzer.analyze_url(url).await?,
+        Source::Stdin => analyzer.analyze_reader(std::io::stdin(), "stdin").await?,
+        Source::Git { dir, rev } => analyzer.analyze_git(dir, rev).await?,
+    };
+
+    if let Some(name) = &cli.name {
+        project_analysis.project_name = name.clone();
+    }
 
     let elapsed = start_time.elapsed();
 
@@ -119,18 +150,30 @@ async fn analyze_remote_archive(url: &str, cli: &Cli) -> Result<()> {
 
 #[cfg(feature = "cli")]
 fn configure_analyzer_filters(analyzer: &mut RemoteAnalyzer, cli: &Cli) -> Result<()> {
-    if cli.aggressive_filter {
-        analyzer.set_aggressive_filtering(true);
+    let mut filter = if cli.aggressive_filter {
+        filter::IntelligentFilter::aggressive()
     } else {
-        let filter = filter::IntelligentFilter {
+        filter::IntelligentFilter {
             max_file_size: cli.max_file_size * 1024,
             ignore_test_dirs: !cli.include_tests,
             ignore_docs_dirs: !cli.include_docs,
             ..filter::IntelligentFilter::default()
-        };
+        }
+    };
 
-        analyzer.set_filter(filter);
-    }
+    filter
+        .custom_ignore_patterns
+        .extend(cli.exclude_pattern.iter().cloned());
+    filter
+        .include_patterns
+        .extend(cli.include_pattern.iter().cloned());
+    filter.respect_bradarignore = !cli.no_bradarignore;
+    filter.respect_gitignore = cli.respect_gitignore;
+    filter.explain = cli.explain_filter;
+    filter.keep_archive_root = cli.keep_archive_root;
+    filter.count_generated = cli.count_generated;
+
+    analyzer.set_filter(filter);
 
     Ok(())
 }
@@ -138,6 +181,9 @@ fn configure_analyzer_filters(analyzer: &mut RemoteAnalyzer, cli: &Cli) -> Resul
 #[cfg(feature = "cli")]
 fn output_results(project_analysis: &analysis::ProjectAnalysis, cli: &Cli) -> Result<()> {
     match cli.format {
+        OutputFormat::Table if cli.explain_filter => {
+            output::print_filter_explanation(project_analysis);
+        }
         OutputFormat::Table => {
             output::print_table_format(project_analysis, cli.detailed, cli.quiet);
         }
@@ -1,6 +1,10 @@
-use super::progress::format_number;
-use crate::core::{analysis::ProjectAnalysis, error::Result};
+use super::progress::{format_bytes, format_number};
+use crate::core::{
+    analysis::{DirectoryNode, ProjectAnalysis},
+    error::Result,
+};
 use colored::Colorize;
+use std::collections::BTreeMap;
 
 fn get_percentage_color(percentage: f64) -> colored::ColoredString {
     let percentage_str = format!("{:.1}%", percentage);
@@ -19,6 +23,28 @@ fn color_number(num: usize) -> colored::ColoredString {
     format_number(num).bright_white()
 }
 
+fn print_directory_node(node: &DirectoryNode, level: usize, total_code_lines: usize) {
+    let share_percentage = if total_code_lines > 0 {
+        (node.metrics.code_lines as f64 / total_code_lines as f64) * 100.0
+    } else {
+        0.0
+    };
+
+    println!(
+        " {:<30} {:>8} {:>12} {:>10} {:>10} {:>5}",
+        format!("{}{}/", "  ".repeat(level), node.name()),
+        color_number(node.metrics.file_count),
+        color_number(node.metrics.total_lines),
+        color_number(node.metrics.code_lines),
+        color_number(node.metrics.comment_lines),
+        get_percentage_color(share_percentage)
+    );
+
+    for child in node.sorted_children() {
+        print_directory_node(child, level + 1, total_code_lines);
+    }
+}
+
 pub fn print_table_format(project_analysis: &ProjectAnalysis, detailed: bool, quiet: bool) {
     let summary = project_analysis.get_summary();
     let language_stats = project_analysis.get_language_statistics();
@@ -28,6 +54,9 @@ pub fn print_table_format(project_analysis: &ProjectAnalysis, detailed: bool, qu
     }
 
     println!(" {:<56} {}", "Project", summary.project_name);
+    if let Some(ref root) = project_analysis.archive_root {
+        println!(" {:<56} {}", "Archive Root", root);
+    }
     println!(
         " {:<56} {}",
         "Total Files",
@@ -48,6 +77,11 @@ pub fn print_table_format(project_analysis: &ProjectAnalysis, detailed: bool, qu
         "Comment Lines",
         color_number(summary.total_comment_lines)
     );
+    println!(
+        " {:<56} {}",
+        "Doc Comment Lines",
+        color_number(summary.total_doc_comment_lines)
+    );
     println!(
         " {:<56} {}",
         "Blank Lines",
@@ -61,6 +95,20 @@ pub fn print_table_format(project_analysis: &ProjectAnalysis, detailed: bool, qu
     if let Some(ref primary) = summary.primary_language {
         println!(" {:<56} {}", "Primary Language", primary);
     }
+    if summary.binary_files > 0 {
+        println!(
+            " {:<56} {} ({})",
+            "Binary Files",
+            color_number(summary.binary_files),
+            format_bytes(summary.binary_size_bytes)
+        );
+    }
+    println!(
+        " {:<56} {} / {}",
+        "Generated / Handwritten Lines",
+        color_number(summary.generated_lines),
+        color_number(summary.handwritten_lines)
+    );
     println!(
         " {:<56} {}",
         "Code Ratio",
@@ -72,6 +120,18 @@ pub fn print_table_format(project_analysis: &ProjectAnalysis, detailed: bool, qu
         format!("{:.1}%", summary.overall_documentation_ratio * 100.0).bold()
     );
 
+    let filter_stats = &project_analysis.filter_stats;
+    if filter_stats.total_entries > 0 {
+        println!(
+            " {:<56} {} / {} ({:.1}% filtered, {} skipped)",
+            "Entries Counted",
+            color_number(filter_stats.processed),
+            color_number(filter_stats.total_entries),
+            filter_stats.filter_ratio() * 100.0,
+            filter_stats.format_bytes_saved()
+        );
+    }
+
     if !language_stats.is_empty() && !quiet {
         println!("{}", "=".repeat(80));
 
@@ -104,6 +164,19 @@ pub fn print_table_format(project_analysis: &ProjectAnalysis, detailed: bool, qu
                 color_number(stats.blank_lines),
                 get_percentage_color(share_percentage)
             );
+
+            for child in &stats.embedded {
+                println!(
+                    " {:<20} {:>8} {:>12} {:>8} {:>10} {:>8} {:>7}",
+                    format!(" |- {}", child.language).dimmed(),
+                    "",
+                    color_number(child.lines.total_lines),
+                    color_number(child.lines.code_lines),
+                    color_number(child.lines.comment_lines),
+                    color_number(child.lines.blank_lines),
+                    ""
+                );
+            }
         }
 
         println!("{}", "=".repeat(80));
@@ -119,6 +192,43 @@ pub fn print_table_format(project_analysis: &ProjectAnalysis, detailed: bool, qu
         );
     }
 
+    let directories = project_analysis.directory_tree.sorted_children();
+    if !directories.is_empty() && !quiet {
+        println!("{}", "=".repeat(80));
+        println!(
+            " {:<30} {:>8} {:>12} {:>10} {:>10} {:>5}",
+            "Directory".bold(),
+            "Files",
+            "Lines",
+            "Code",
+            "Comments",
+            "%"
+        );
+        println!("{}", "=".repeat(80));
+        for directory in directories {
+            print_directory_node(directory, 0, summary.total_code_lines);
+        }
+    }
+
+    if !filter_stats.by_reason.is_empty() && !quiet {
+        println!("{}", "=".repeat(80));
+        println!(
+            " {:<30} {:>12} {:>16}",
+            "Filtered".bold(),
+            "Entries",
+            "Bytes"
+        );
+        println!("{}", "=".repeat(80));
+        for (reason, stats) in &filter_stats.by_reason {
+            println!(
+                " {:<30} {:>12} {:>16}",
+                reason,
+                color_number(stats.entries),
+                color_number(stats.bytes as usize)
+            );
+        }
+    }
+
     if detailed && !quiet {
         println!("{}", "=".repeat(80));
 
@@ -135,12 +245,77 @@ pub fn print_table_format(project_analysis: &ProjectAnalysis, detailed: bool, qu
                         color_number(file.code_lines),
                         color_number(file.comment_lines)
                     );
+
+                    if !file.ambiguous_with.is_empty() {
+                        println!(
+                            "     {}",
+                            format!(
+                                "ambiguous extension, could also be {}",
+                                file.ambiguous_with.join(", ")
+                            )
+                            .dimmed()
+                        );
+                    }
+
+                    if file.generated {
+                        println!("     {}", "generated".dimmed());
+                    }
+
+                    if let Some(encoding) = file.encoding.as_deref().filter(|&e| e != "UTF-8") {
+                        println!("     {}", format!("decoded from {}", encoding).dimmed());
+                    }
                 }
             }
         }
     }
 }
 
+pub fn print_filter_explanation(project_analysis: &ProjectAnalysis) {
+    let decisions = &project_analysis.filter_decisions;
+    let mut totals: BTreeMap<&str, (usize, u64)> = BTreeMap::new();
+
+    println!("{}", "=".repeat(80));
+    println!(" {:<56} {}", "Project", project_analysis.project_name);
+    println!("{}", "=".repeat(80));
+
+    for entry in decisions {
+        let decision = entry.decision.to_string();
+        let decision = if entry.decision.is_included() {
+            decision.bright_green()
+        } else {
+            decision.dimmed()
+        };
+        println!(" {:<56} {}", entry.path, decision);
+
+        let total = totals.entry(entry.decision.reason()).or_default();
+        total.0 += 1;
+        total.1 += entry.size_bytes;
+    }
+
+    println!("{}", "=".repeat(80));
+    println!(
+        " {:<30} {:>12} {:>16}",
+        "Decision".bold(),
+        "Entries".bold(),
+        "Bytes".bold()
+    );
+    println!("{}", "=".repeat(80));
+    for (reason, (count, bytes)) in &totals {
+        println!(
+            " {:<30} {:>12} {:>16}",
+            reason,
+            color_number(*count),
+            color_number(*bytes as usize)
+        );
+    }
+    println!("{}", "=".repeat(80));
+    println!(
+        " {:<30} {:>12}",
+        "Total".bold(),
+        color_number(decisions.len())
+    );
+}
+
 pub fn print_json_format(project_analysis: &ProjectAnalysis) -> Result<()> {
     let json = serde_json::to_string_pretty(project_analysis)?;
     println!("{}", json);
@@ -185,6 +360,9 @@ pub fn print_xml_format(project_analysis: &ProjectAnalysis) -> Result<()> {
         "  <project_name>{}</project_name>",
         xml_escape(&summary.project_name)
     );
+    if let Some(ref root) = project_analysis.archive_root {
+        println!("  <archive_root>{}</archive_root>", xml_escape(root));
+    }
 
     println!("  <summary>");
     println!("    <total_files>{}</total_files>", summary.total_files);
@@ -197,6 +375,10 @@ pub fn print_xml_format(project_analysis: &ProjectAnalysis) -> Result<()> {
         "    <total_comment_lines>{}</total_comment_lines>",
         summary.total_comment_lines
     );
+    println!(
+        "    <total_doc_comment_lines>{}</total_doc_comment_lines>",
+        summary.total_doc_comment_lines
+    );
     println!(
         "    <total_blank_lines>{}</total_blank_lines>",
         summary.total_blank_lines
@@ -213,6 +395,24 @@ pub fn print_xml_format(project_analysis: &ProjectAnalysis) -> Result<()> {
         );
     }
 
+    println!("    <binary_files>{}</binary_files>", summary.binary_files);
+    println!(
+        "    <binary_size_bytes>{}</binary_size_bytes>",
+        summary.binary_size_bytes
+    );
+    println!(
+        "    <generated_files>{}</generated_files>",
+        summary.generated_files
+    );
+    println!(
+        "    <generated_lines>{}</generated_lines>",
+        summary.generated_lines
+    );
+    println!(
+        "    <handwritten_lines>{}</handwritten_lines>",
+        summary.handwritten_lines
+    );
+
     println!(
         "    <overall_complexity_ratio>{:.6}</overall_complexity_ratio>",
         summary.overall_complexity_ratio
@@ -234,15 +434,69 @@ pub fn print_xml_format(project_analysis: &ProjectAnalysis) -> Result<()> {
             "      <comment_lines>{}</comment_lines>",
             stats.comment_lines
         );
+        println!(
+            "      <doc_comment_lines>{}</doc_comment_lines>",
+            stats.doc_comment_lines
+        );
         println!("      <blank_lines>{}</blank_lines>", stats.blank_lines);
         println!(
             "      <complexity_ratio>{:.6}</complexity_ratio>",
             stats.complexity_ratio
         );
+        if !stats.embedded.is_empty() {
+            println!("      <embedded>");
+            for child in &stats.embedded {
+                println!("        <language>");
+                println!("          <n>{}</n>", xml_escape(&child.language));
+                println!(
+                    "          <total_lines>{}</total_lines>",
+                    child.lines.total_lines
+                );
+                println!(
+                    "          <code_lines>{}</code_lines>",
+                    child.lines.code_lines
+                );
+                println!(
+                    "          <comment_lines>{}</comment_lines>",
+                    child.lines.comment_lines
+                );
+                println!(
+                    "          <blank_lines>{}</blank_lines>",
+                    child.lines.blank_lines
+                );
+                println!("        </language>");
+            }
+            println!("      </embedded>");
+        }
         println!("    </language>");
     }
     println!("  </language_statistics>");
 
+    let filter_stats = &project_analysis.filter_stats;
+    println!("  <filter_stats>");
+    println!(
+        "    <total_entries>{}</total_entries>",
+        filter_stats.total_entries
+    );
+    println!("    <processed>{}</processed>", filter_stats.processed);
+    println!(
+        "    <filtered_out>{}</filtered_out>",
+        filter_stats.filtered_out
+    );
+    println!(
+        "    <bytes_saved>{}</bytes_saved>",
+        filter_stats.bytes_saved
+    );
+    for (reason, stats) in &filter_stats.by_reason {
+        println!(
+            "    <reason name=\"{}\" entries=\"{}\" bytes=\"{}\"/>",
+            xml_escape(reason),
+            stats.entries,
+            stats.bytes
+        );
+    }
+    println!("  </filter_stats>");
+
     println!("</project_analysis>");
     Ok(())
 }
@@ -31,7 +31,7 @@ pub fn format_number(num: usize) -> String {
     let chars: Vec<char> = num_str.chars().collect();
 
     for (i, ch) in chars.iter().enumerate() {
-        if i > 0 && (chars.len() - i) % 3 == 0 {
+        if i > 0 && (chars.len() - i).is_multiple_of(3) {
             result.push(',');
         }
         result.push(*ch);
@@ -104,8 +104,7 @@ impl ProgressHook for ProgressBarHook {
     }
 
     fn on_processing_progress(&self, current: usize, total: usize) {
-        if total > 0 {
-            let percentage = (current * 100) / total;
+        if let Some(percentage) = (current * 100).checked_div(total) {
             self.progress_bar.set_message(format!(
                 "Processing files: {}% ({}/{})",
                 percentage, current, total
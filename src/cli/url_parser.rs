@@ -3,6 +3,21 @@ pub fn expand_url(url: &str) -> String {
         return url.to_string();
     }
 
+    // Local directories and archives are shown as given.
+    if url.starts_with("file://") || std::path::Path::new(url).exists() {
+        return url.to_string();
+    }
+
+    // `user/repo@ref:path` shows as the tree URL of that directory.
+    if let Some((repo, subpath)) = url.split_once(':') {
+        let expanded = expand_url(repo);
+        let subpath = subpath.trim_matches('/');
+        return match expanded.replacen("/commit/", "/tree/", 1) {
+            tree if tree.contains("/tree/") => format!("{}/{}", tree, subpath),
+            _ => format!("{} ({})", expanded, subpath),
+        };
+    }
+
     if url.contains('/') && !url.starts_with("http://") && !url.starts_with("https://") {
         let parts: Vec<&str> = url.split('@').collect();
         let repo_part = parts[0];
@@ -28,13 +43,14 @@ pub fn expand_url(url: &str) -> String {
 pub fn show_usage_examples() {
     println!("Error: URL argument is required");
     println!();
-    println!("Usage: bradar <URL>");
+    println!("Usage: bradar <URL|PATH>");
     println!();
     println!("Examples:");
     println!("  # GitHub repositories");
     println!("  bradar user/repo                    # Default branch");
     println!("  bradar user/repo@master             # Specific branch");
     println!("  bradar user/repo@abc123             # Specific commit");
+    println!("  bradar user/repo@main:services/api  # Single directory");
     println!("  bradar https://github.com/user/repo # Full GitHub URL");
     println!();
     println!("  # Other platforms");
@@ -42,6 +58,11 @@ pub fn show_usage_examples() {
     println!("  bradar https://bitbucket.org/user/repo # Bitbucket");
     println!("  bradar https://codeberg.org/user/repo # Codeberg");
     println!();
+    println!("  # Local sources");
+    println!("  bradar .                            # Current directory");
+    println!("  bradar ./release.tar.gz             # Archive on disk");
+    println!("  bradar file:///tmp/src.zip          # file:// URL");
+    println!();
     println!("  # Output formats");
     println!("  bradar -f json user/repo            # JSON output");
     println!("  bradar -f csv user/repo             # CSV output");
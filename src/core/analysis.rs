@@ -1,11 +1,14 @@
+use crate::counter::LineCounts;
 use crate::error::{AnalysisError, Result};
+use crate::filter::{EntryDecision, FilterStats};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::{Display, Formatter};
 use std::path::Path;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum FileCategory {
+    #[default]
     Source,
     Documentation,
     Configuration,
@@ -15,12 +18,6 @@ pub enum FileCategory {
     Build,
 }
 
-impl Default for FileCategory {
-    fn default() -> Self {
-        Self::Source
-    }
-}
-
 impl Display for FileCategory {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let category = match self {
@@ -36,16 +33,50 @@ impl Display for FileCategory {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmbeddedMetrics {
+    pub language: String,
+    #[serde(flatten)]
+    pub lines: LineCounts,
+}
+
+impl EmbeddedMetrics {
+    pub fn new<L: Into<String>>(language: L) -> Self {
+        Self {
+            language: language.into(),
+            lines: LineCounts::default(),
+        }
+    }
+
+    pub fn incorporate(&mut self, other: &EmbeddedMetrics) {
+        self.lines.total_lines += other.lines.total_lines;
+        self.lines.code_lines += other.lines.code_lines;
+        self.lines.comment_lines += other.lines.comment_lines;
+        self.lines.doc_comment_lines += other.lines.doc_comment_lines;
+        self.lines.blank_lines += other.lines.blank_lines;
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileMetrics {
     pub file_path: String,
     pub total_lines: usize,
     pub code_lines: usize,
     pub comment_lines: usize,
+    #[serde(default)]
+    pub doc_comment_lines: usize,
     pub blank_lines: usize,
     pub category: FileCategory,
     pub language: String,
     pub size_bytes: u64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub embedded: Vec<EmbeddedMetrics>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ambiguous_with: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<String>,
+    #[serde(default)]
+    pub generated: bool,
 }
 
 impl FileMetrics {
@@ -72,13 +103,37 @@ impl FileMetrics {
             total_lines,
             code_lines,
             comment_lines,
+            doc_comment_lines: 0,
             blank_lines,
             category: FileCategory::default(),
             language,
             size_bytes: 0,
+            embedded: Vec::new(),
+            ambiguous_with: Vec::new(),
+            encoding: None,
+            generated: false,
         })
     }
 
+    /// Metrics for a binary file, which has a size but no lines.
+    pub fn binary<P: AsRef<Path>>(file_path: P, size_bytes: u64) -> Self {
+        Self {
+            file_path: file_path.as_ref().to_string_lossy().to_string(),
+            total_lines: 0,
+            code_lines: 0,
+            comment_lines: 0,
+            doc_comment_lines: 0,
+            blank_lines: 0,
+            category: FileCategory::Binary,
+            language: FileCategory::Binary.to_string(),
+            size_bytes,
+            embedded: Vec::new(),
+            ambiguous_with: Vec::new(),
+            encoding: None,
+            generated: false,
+        }
+    }
+
     pub fn with_category(mut self, category: FileCategory) -> Self {
         self.category = category;
         self
@@ -89,6 +144,33 @@ impl FileMetrics {
         self
     }
 
+    pub fn with_doc_comment_lines(mut self, doc_comment_lines: usize) -> Self {
+        self.doc_comment_lines = doc_comment_lines;
+        self
+    }
+
+    pub fn with_embedded(mut self, embedded: Vec<EmbeddedMetrics>) -> Self {
+        self.embedded = embedded;
+        self
+    }
+
+    pub fn with_ambiguous_with(mut self, ambiguous_with: Vec<String>) -> Self {
+        self.ambiguous_with = ambiguous_with;
+        self
+    }
+
+    /// Records the encoding the file's text was decoded from.
+    pub fn with_encoding<E: Into<String>>(mut self, encoding: E) -> Self {
+        self.encoding = Some(encoding.into());
+        self
+    }
+
+    /// Marks the file as produced by a code generator.
+    pub fn with_generated(mut self, generated: bool) -> Self {
+        self.generated = generated;
+        self
+    }
+
     pub fn complexity_ratio(&self) -> f64 {
         if self.total_lines == 0 {
             0.0
@@ -124,6 +206,12 @@ impl FileMetrics {
             ));
         }
 
+        if self.doc_comment_lines > self.comment_lines {
+            return Err(AnalysisError::invalid_statistics(
+                "Doc comment lines exceed comment lines",
+            ));
+        }
+
         Ok(())
     }
 }
@@ -175,12 +263,22 @@ impl LanguageAnalysis {
     }
 
     pub fn calculate_statistics(&self) -> LanguageStatistics {
+        let mut embedded: Vec<EmbeddedMetrics> = Vec::new();
+        for child in self.file_metrics.iter().flat_map(|m| &m.embedded) {
+            match embedded.iter_mut().find(|e| e.language == child.language) {
+                Some(existing) => existing.incorporate(child),
+                None => embedded.push(child.clone()),
+            }
+        }
+        embedded.sort_by_key(|e| std::cmp::Reverse(e.lines.total_lines));
+
         LanguageStatistics {
             language_name: self.language_name.clone(),
             file_count: self.file_metrics.len(),
             total_lines: self.aggregate_metrics.total_lines,
             code_lines: self.aggregate_metrics.code_lines,
             comment_lines: self.aggregate_metrics.comment_lines,
+            doc_comment_lines: self.aggregate_metrics.doc_comment_lines,
             blank_lines: self.aggregate_metrics.blank_lines,
             total_size_bytes: self.aggregate_metrics.total_size_bytes,
             average_file_size: if self.file_metrics.is_empty() {
@@ -190,6 +288,7 @@ impl LanguageAnalysis {
             },
             complexity_ratio: self.aggregate_metrics.complexity_ratio(),
             documentation_ratio: self.aggregate_metrics.documentation_ratio(),
+            embedded,
         }
     }
 }
@@ -199,6 +298,8 @@ pub struct AggregateMetrics {
     pub total_lines: usize,
     pub code_lines: usize,
     pub comment_lines: usize,
+    #[serde(default)]
+    pub doc_comment_lines: usize,
     pub blank_lines: usize,
     pub total_size_bytes: u64,
     pub file_count: usize,
@@ -209,6 +310,7 @@ impl AggregateMetrics {
         self.total_lines += metrics.total_lines;
         self.code_lines += metrics.code_lines;
         self.comment_lines += metrics.comment_lines;
+        self.doc_comment_lines += metrics.doc_comment_lines;
         self.blank_lines += metrics.blank_lines;
         self.total_size_bytes += metrics.size_bytes;
         self.file_count += 1;
@@ -231,6 +333,71 @@ impl AggregateMetrics {
     }
 }
 
+/// Default number of directory levels rolled up in
+/// [`ProjectAnalysis::directory_tree`].
+pub const DEFAULT_DIRECTORY_DEPTH: usize = 2;
+
+/// Metrics for a directory and everything below it. Files deeper than the
+/// tree's depth are counted in their nearest ancestor that is kept.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DirectoryNode {
+    pub path: String,
+    pub metrics: AggregateMetrics,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub children: BTreeMap<String, DirectoryNode>,
+}
+
+impl DirectoryNode {
+    pub fn new<P: Into<String>>(path: P) -> Self {
+        Self {
+            path: path.into(),
+            metrics: AggregateMetrics::default(),
+            children: BTreeMap::new(),
+        }
+    }
+
+    /// Adds `metrics` to this node and to the directories on the file's path,
+    /// creating up to `depth` levels of child nodes.
+    pub fn incorporate(&mut self, metrics: &FileMetrics, depth: usize) {
+        self.metrics.incorporate(metrics);
+
+        let directories = metrics
+            .file_path
+            .rsplit_once('/')
+            .map(|(directory, _)| directory)
+            .unwrap_or("");
+
+        let mut node = self;
+        for name in directories
+            .split('/')
+            .filter(|name| !name.is_empty())
+            .take(depth)
+        {
+            let path = if node.path.is_empty() {
+                name.to_string()
+            } else {
+                format!("{}/{}", node.path, name)
+            };
+            node = node
+                .children
+                .entry(name.to_string())
+                .or_insert_with(|| DirectoryNode::new(path));
+            node.metrics.incorporate(metrics);
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        self.path.rsplit('/').next().unwrap_or(&self.path)
+    }
+
+    /// Child directories, largest by code lines first.
+    pub fn sorted_children(&self) -> Vec<&DirectoryNode> {
+        let mut children: Vec<_> = self.children.values().collect();
+        children.sort_by_key(|child| std::cmp::Reverse(child.metrics.code_lines));
+        children
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LanguageStatistics {
     pub language_name: String,
@@ -238,11 +405,15 @@ pub struct LanguageStatistics {
     pub total_lines: usize,
     pub code_lines: usize,
     pub comment_lines: usize,
+    #[serde(default)]
+    pub doc_comment_lines: usize,
     pub blank_lines: usize,
     pub total_size_bytes: u64,
     pub average_file_size: f64,
     pub complexity_ratio: f64,
     pub documentation_ratio: f64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub embedded: Vec<EmbeddedMetrics>,
 }
 
 impl Display for LanguageStatistics {
@@ -267,6 +438,20 @@ pub struct ProjectAnalysis {
     pub project_name: String,
     pub language_analyses: HashMap<String, LanguageAnalysis>,
     pub global_metrics: AggregateMetrics,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archive_root: Option<String>,
+    #[serde(default)]
+    pub directory_depth: usize,
+    #[serde(default)]
+    pub directory_tree: DirectoryNode,
+    #[serde(default)]
+    pub filter_stats: FilterStats,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub filter_decisions: Vec<EntryDecision>,
+    /// Binary files, which have a size but no lines. They are kept out of
+    /// the language analyses, the directory tree and the totals.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub binary_files: Vec<FileMetrics>,
 }
 
 impl ProjectAnalysis {
@@ -275,19 +460,39 @@ impl ProjectAnalysis {
             project_name: project_name.into(),
             language_analyses: HashMap::new(),
             global_metrics: AggregateMetrics::default(),
+            archive_root: None,
+            directory_depth: DEFAULT_DIRECTORY_DEPTH,
+            directory_tree: DirectoryNode::default(),
+            filter_stats: FilterStats::new(),
+            filter_decisions: Vec::new(),
+            binary_files: Vec::new(),
         }
     }
 
+    /// Sets how many directory levels the directory tree keeps. Must be set
+    /// before files are added; `0` only keeps the project root.
+    pub fn with_directory_depth(mut self, depth: usize) -> Self {
+        self.directory_depth = depth;
+        self
+    }
+
     pub fn add_file_metrics(&mut self, metrics: FileMetrics) -> Result<()> {
         metrics.validate()?;
 
+        if metrics.category == FileCategory::Binary {
+            self.binary_files.push(metrics);
+            return Ok(());
+        }
+
         let language_analysis = self
             .language_analyses
             .entry(metrics.language.clone())
             .or_insert_with(|| LanguageAnalysis::new(metrics.language.clone()));
 
-        language_analysis.add_file_metrics(metrics.clone())?;
         self.global_metrics.incorporate(&metrics);
+        self.directory_tree
+            .incorporate(&metrics, self.directory_depth);
+        language_analysis.add_file_metrics(metrics)?;
 
         Ok(())
     }
@@ -299,12 +504,26 @@ impl ProjectAnalysis {
             .map(|analysis| analysis.calculate_statistics())
             .collect();
 
-        stats.sort_by(|a, b| b.total_lines.cmp(&a.total_lines));
+        stats.sort_by_key(|s| std::cmp::Reverse(s.total_lines));
         stats
     }
 
     pub fn get_summary(&self) -> ProjectSummary {
         let language_stats = self.get_language_statistics();
+        let (counted_files, counted_lines) = self
+            .language_analyses
+            .values()
+            .flat_map(|analysis| &analysis.file_metrics)
+            .filter(|file| file.generated)
+            .fold((0, 0), |(files, lines), file| {
+                (files + 1, lines + file.total_lines)
+            });
+        // Generated files left out by the filter still count towards the split.
+        let excluded_files = self
+            .filter_stats
+            .by_reason
+            .get("generated")
+            .map_or(0, |reason| reason.entries);
 
         ProjectSummary {
             project_name: self.project_name.clone(),
@@ -312,8 +531,14 @@ impl ProjectAnalysis {
             total_lines: self.global_metrics.total_lines,
             total_code_lines: self.global_metrics.code_lines,
             total_comment_lines: self.global_metrics.comment_lines,
+            total_doc_comment_lines: self.global_metrics.doc_comment_lines,
             total_blank_lines: self.global_metrics.blank_lines,
             total_size_bytes: self.global_metrics.total_size_bytes,
+            binary_files: self.binary_files.len(),
+            binary_size_bytes: self.binary_files.iter().map(|file| file.size_bytes).sum(),
+            generated_files: counted_files + excluded_files,
+            generated_lines: counted_lines + self.filter_stats.generated_lines,
+            handwritten_lines: self.global_metrics.total_lines - counted_lines,
             language_count: self.language_analyses.len(),
             primary_language: language_stats.first().map(|s| s.language_name.clone()),
             overall_complexity_ratio: self.global_metrics.complexity_ratio(),
@@ -329,8 +554,20 @@ pub struct ProjectSummary {
     pub total_lines: usize,
     pub total_code_lines: usize,
     pub total_comment_lines: usize,
+    #[serde(default)]
+    pub total_doc_comment_lines: usize,
     pub total_blank_lines: usize,
     pub total_size_bytes: u64,
+    #[serde(default)]
+    pub binary_files: usize,
+    #[serde(default)]
+    pub binary_size_bytes: u64,
+    #[serde(default)]
+    pub generated_files: usize,
+    #[serde(default)]
+    pub generated_lines: usize,
+    #[serde(default)]
+    pub handwritten_lines: usize,
     pub language_count: usize,
     pub primary_language: Option<String>,
     pub overall_complexity_ratio: f64,
@@ -0,0 +1,153 @@
+//! `.gitattributes` support for the linguist attributes GitHub uses to
+//! correct its language statistics.
+
+use super::glob::glob_match;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinguistAttributes {
+    pub language: Option<String>,
+    pub vendored: Option<bool>,
+    pub generated: Option<bool>,
+    pub documentation: Option<bool>,
+}
+
+impl LinguistAttributes {
+    /// Whether GitHub would leave the file out of its language statistics.
+    pub fn is_excluded(&self) -> bool {
+        self.excluded_by().is_some()
+    }
+
+    /// The attribute that excludes the file, if any.
+    pub fn excluded_by(&self) -> Option<&'static str> {
+        if self.vendored == Some(true) {
+            Some("linguist-vendored")
+        } else if self.generated == Some(true) {
+            Some("linguist-generated")
+        } else if self.documentation == Some(true) {
+            Some("linguist-documentation")
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct AttributeRule {
+    base: String,
+    depth: usize,
+    pattern: String,
+    assignments: Vec<(String, Option<String>)>,
+}
+
+/// Attribute rules collected from every `.gitattributes` seen so far.
+#[derive(Debug, Clone, Default)]
+pub struct GitAttributes {
+    rules: Vec<AttributeRule>,
+}
+
+impl GitAttributes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Adds the rules of the `.gitattributes` file at `path`. Its patterns are
+    /// relative to the directory that contains it.
+    pub fn add_file(&mut self, path: &str, content: &str) {
+        let base = match path.rfind('/') {
+            Some(index) => &path[..=index],
+            None => "",
+        };
+        let depth = base.matches('/').count();
+
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut fields = line.split_whitespace();
+            let Some(pattern) = fields.next() else {
+                continue;
+            };
+
+            let assignments: Vec<(String, Option<String>)> =
+                fields.filter_map(parse_assignment).collect();
+            if assignments.is_empty() {
+                continue;
+            }
+
+            self.rules.push(AttributeRule {
+                base: base.to_string(),
+                depth,
+                pattern: pattern.to_string(),
+                assignments,
+            });
+        }
+
+        // Deeper files take precedence, and later lines within a file win, so
+        // rules are applied shallowest first in the order they were read.
+        self.rules.sort_by_key(|rule| rule.depth);
+    }
+
+    pub fn attributes_for(&self, path: &str) -> LinguistAttributes {
+        let mut attributes = LinguistAttributes::default();
+
+        for rule in &self.rules {
+            let Some(relative) = path.strip_prefix(rule.base.as_str()) else {
+                continue;
+            };
+            if !rule.matches(relative) {
+                continue;
+            }
+
+            for (name, value) in &rule.assignments {
+                let flag = value.as_deref().map(|v| v != "false");
+                match name.as_str() {
+                    "linguist-language" => attributes.language = value.clone(),
+                    "linguist-vendored" => attributes.vendored = flag,
+                    "linguist-generated" => attributes.generated = flag,
+                    "linguist-documentation" => attributes.documentation = flag,
+                    _ => {}
+                }
+            }
+        }
+
+        attributes
+    }
+}
+
+impl AttributeRule {
+    fn matches(&self, relative: &str) -> bool {
+        let pattern = self.pattern.strip_prefix('/').unwrap_or(&self.pattern);
+
+        // Patterns without a slash match the file name at any depth.
+        if !self.pattern.contains('/') {
+            let name = relative.rsplit('/').next().unwrap_or(relative);
+            return glob_match(pattern, name);
+        }
+
+        glob_match(pattern, relative)
+    }
+}
+
+// `attr` sets, `-attr` clears, `attr=value` assigns and `!attr` returns the
+// attribute to unspecified, which is recorded as `None`.
+fn parse_assignment(field: &str) -> Option<(String, Option<String>)> {
+    let (name, value) = if let Some(name) = field.strip_prefix('-') {
+        (name, Some("false".to_string()))
+    } else if let Some(name) = field.strip_prefix('!') {
+        (name, None)
+    } else if let Some((name, value)) = field.split_once('=') {
+        (name, Some(value.to_string()))
+    } else {
+        (field, Some("true".to_string()))
+    };
+
+    name.starts_with("linguist-")
+        .then(|| (name.to_string(), value))
+}
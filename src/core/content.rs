@@ -0,0 +1,83 @@
+//! Content sniffing for files whose extension doesn't say what they hold,
+//! and decoding of text that isn't UTF-8.
+
+use encoding_rs::{Encoding, SHIFT_JIS, UTF_8, WINDOWS_1252};
+use std::borrow::Cow;
+
+/// How much of a file is inspected, matching what git looks at.
+pub const SNIFF_LEN: usize = 8000;
+
+// Share of suspicious bytes above which a NUL-free sample counts as binary.
+const MAX_SUSPICIOUS_RATIO: f64 = 0.3;
+
+/// Whether the start of a file looks like binary data: it contains a NUL
+/// byte, or too many of its bytes are invalid UTF-8 or control characters.
+pub fn is_binary_content(content: &[u8]) -> bool {
+    let sample = &content[..content.len().min(SNIFF_LEN)];
+    if sample.is_empty() {
+        return false;
+    }
+    // A byte order mark only starts text, and UTF-16 text is full of NULs.
+    if Encoding::for_bom(sample).is_some() {
+        return false;
+    }
+    if sample.contains(&0) {
+        return true;
+    }
+
+    // Shift-JIS text is mostly invalid UTF-8, so only control characters
+    // give binary data away there.
+    let legacy_text = detect_encoding(content) == SHIFT_JIS;
+
+    let mut suspicious = 0;
+    for chunk in sample.utf8_chunks() {
+        suspicious += chunk
+            .valid()
+            .chars()
+            .filter(|&c| c.is_control() && !matches!(c, '\t' | '\n' | '\r' | '\x0c' | '\x1b'))
+            .count();
+        if !legacy_text {
+            suspicious += chunk.invalid().len();
+        }
+    }
+
+    // A multi-byte character cut off by the sample boundary isn't invalid.
+    if !legacy_text && sample.len() < content.len() {
+        let cut = sample
+            .utf8_chunks()
+            .last()
+            .map_or(0, |chunk| chunk.invalid().len());
+        suspicious -= cut.min(3);
+    }
+
+    suspicious as f64 / sample.len() as f64 > MAX_SUSPICIOUS_RATIO
+}
+
+/// The encoding a text file is most likely in: the one its byte order mark
+/// names, UTF-8 if it is valid, Shift-JIS if it decodes cleanly to Japanese
+/// kana, and Windows-1252 (a superset of Latin-1) otherwise.
+pub fn detect_encoding(content: &[u8]) -> &'static Encoding {
+    if let Some((encoding, _)) = Encoding::for_bom(content) {
+        return encoding;
+    }
+    if std::str::from_utf8(content).is_ok() {
+        return UTF_8;
+    }
+
+    // Kana lead bytes are rare in Latin-1 text, while accented letters
+    // followed by ASCII often pass for Shift-JIS kanji.
+    let (text, had_errors) = SHIFT_JIS.decode_without_bom_handling(content);
+    if !had_errors && text.chars().any(|c| matches!(c, '\u{3041}'..='\u{30ff}')) {
+        return SHIFT_JIS;
+    }
+
+    WINDOWS_1252
+}
+
+/// Decodes a text file with [`detect_encoding`], dropping any byte order
+/// mark and replacing bytes that can't be decoded. Returns the text and the
+/// name of the encoding used.
+pub fn decode_text(content: &[u8]) -> (Cow<'_, str>, &'static str) {
+    let (text, encoding, _) = detect_encoding(content).decode(content);
+    (text, encoding.name())
+}
@@ -0,0 +1,606 @@
+use crate::analysis::EmbeddedMetrics;
+use crate::registry::{
+    EmbeddedRegion, LanguageDefinition, LanguageRegistry, LineCommentPosition, LiterateStyle,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineKind {
+    Blank,
+    Code,
+    Comment,
+    DocComment,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LineCounts {
+    pub total_lines: usize,
+    pub code_lines: usize,
+    pub comment_lines: usize,
+    pub doc_comment_lines: usize,
+    pub blank_lines: usize,
+}
+
+impl LineCounts {
+    pub fn record(&mut self, kind: LineKind) {
+        self.total_lines += 1;
+        match kind {
+            LineKind::Blank => self.blank_lines += 1,
+            LineKind::Code => self.code_lines += 1,
+            LineKind::Comment => self.comment_lines += 1,
+            LineKind::DocComment => {
+                self.comment_lines += 1;
+                self.doc_comment_lines += 1;
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenKind {
+    LineComment,
+    BlockComment,
+    Quote,
+    DocQuote,
+    VerbatimQuote,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Token<'a> {
+    start: &'a str,
+    end: &'a str,
+    kind: TokenKind,
+    nested: bool,
+    doc: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State<'a> {
+    Code,
+    String {
+        end: &'a str,
+        verbatim: bool,
+        doc: bool,
+    },
+    Comment,
+}
+
+struct OpenRegion<'a> {
+    region: &'a EmbeddedRegion,
+    language: Option<&'static str>,
+    counter: LineCounter<'a>,
+    depth: usize,
+}
+
+/// Per-line state machine that classifies lines as code, comment or blank
+/// while tracking string literals and comment regions across lines.
+pub struct LineCounter<'a> {
+    tokens: Vec<Token<'a>>,
+    regions: &'a [EmbeddedRegion],
+    char_literals: bool,
+    case_sensitive: bool,
+    line_comments_at_start: bool,
+    literate: Option<LiterateStyle>,
+    in_code_block: bool,
+    state: State<'a>,
+    comment_stack: Vec<Token<'a>>,
+}
+
+impl<'a> LineCounter<'a> {
+    pub fn new(language: Option<&'a LanguageDefinition>) -> Self {
+        let mut tokens = Vec::new();
+
+        if let Some(lang) = language {
+            for marker in &lang.line_comments {
+                tokens.push(Token {
+                    start: marker,
+                    end: "",
+                    kind: TokenKind::LineComment,
+                    nested: false,
+                    doc: false,
+                });
+            }
+
+            for (start, end) in &lang.multi_line_comments {
+                tokens.push(Token {
+                    start,
+                    end,
+                    kind: TokenKind::BlockComment,
+                    nested: lang.is_nested,
+                    doc: false,
+                });
+            }
+
+            for (start, end) in &lang.nested_comments {
+                tokens.push(Token {
+                    start,
+                    end,
+                    kind: TokenKind::BlockComment,
+                    nested: true,
+                    doc: false,
+                });
+            }
+
+            for marker in &lang.doc_line_comments {
+                tokens.push(Token {
+                    start: marker,
+                    end: "",
+                    kind: TokenKind::LineComment,
+                    nested: false,
+                    doc: true,
+                });
+            }
+
+            for (start, end) in &lang.doc_multi_line_comments {
+                tokens.push(Token {
+                    start,
+                    end,
+                    kind: TokenKind::BlockComment,
+                    nested: lang.is_nested,
+                    doc: true,
+                });
+            }
+
+            for (start, end) in &lang.quotes {
+                tokens.push(Token {
+                    start,
+                    end,
+                    kind: TokenKind::Quote,
+                    nested: false,
+                    doc: false,
+                });
+            }
+
+            for (start, end) in &lang.doc_quotes {
+                tokens.push(Token {
+                    start,
+                    end,
+                    kind: TokenKind::DocQuote,
+                    nested: false,
+                    doc: true,
+                });
+            }
+
+            for (start, end) in &lang.verbatim_quotes {
+                tokens.push(Token {
+                    start,
+                    end,
+                    kind: TokenKind::VerbatimQuote,
+                    nested: false,
+                    doc: false,
+                });
+            }
+        }
+
+        tokens.retain(|token| !token.start.is_empty());
+        // Longest marker wins, so `"""` is preferred over `"` and `###` over `#`.
+        tokens.sort_by_key(|token| std::cmp::Reverse(token.start.len()));
+
+        // Languages that don't quote with `'` commonly use it for character
+        // literals such as `'"'`, which must not open a string.
+        let char_literals = !tokens.is_empty()
+            && !tokens
+                .iter()
+                .any(|t| t.kind != TokenKind::LineComment && t.start.starts_with('\''));
+
+        // Prose formats such as Markdown are flagged literate but carry no
+        // code syntax of their own, so they keep plain line counting.
+        let literate = language
+            .filter(|lang| lang.is_literate && !tokens.is_empty())
+            .map(|lang| lang.literate_style.clone());
+
+        Self {
+            tokens,
+            regions: language.map_or(&[], |lang| lang.embedded.as_slice()),
+            char_literals,
+            case_sensitive: language.is_none_or(|lang| lang.case_sensitive),
+            line_comments_at_start: language
+                .is_some_and(|lang| lang.line_comment_position == LineCommentPosition::Start),
+            // Indented blocks may start the file, but not follow prose.
+            in_code_block: literate == Some(LiterateStyle::IndentedBlocks),
+            literate,
+            state: State::Code,
+            comment_stack: Vec::new(),
+        }
+    }
+
+    pub fn count(content: &str, language: Option<&'a LanguageDefinition>) -> LineCounts {
+        Self::count_with_embedded(content, language).0
+    }
+
+    /// Counts a file whose language declares embedded regions, returning the
+    /// file totals together with the lines attributed to each child language.
+    pub fn count_with_embedded(
+        content: &str,
+        language: Option<&'a LanguageDefinition>,
+    ) -> (LineCounts, Vec<EmbeddedMetrics>) {
+        let mut counter = Self::new(language);
+        let mut counts = LineCounts::default();
+        let mut embedded: Vec<EmbeddedMetrics> = Vec::new();
+        let mut open: Option<OpenRegion<'a>> = None;
+
+        for line in content.lines() {
+            if let Some(current) = open.as_mut() {
+                let trimmed = line.trim_start();
+                let region = current.region;
+
+                if trimmed.starts_with(region.end.as_str()) {
+                    if current.depth == 0 {
+                        open = None;
+                        counts.record(counter.classify_line(line));
+                        continue;
+                    }
+                    current.depth -= 1;
+                } else if region.start != region.end && starts_with_tag(trimmed, &region.start) {
+                    current.depth += 1;
+                }
+
+                let kind = current.counter.classify_line(line);
+                counts.record(kind);
+
+                if let Some(name) = current.language {
+                    match embedded.iter_mut().find(|m| m.language == name) {
+                        Some(metrics) => metrics.lines.record(kind),
+                        None => {
+                            let mut metrics = EmbeddedMetrics::new(name);
+                            metrics.lines.record(kind);
+                            embedded.push(metrics);
+                        }
+                    }
+                }
+                continue;
+            }
+
+            if counter.state == State::Code {
+                open = counter.open_region(line);
+            }
+            counts.record(counter.classify_line(line));
+        }
+
+        (counts, embedded)
+    }
+
+    fn open_region(&self, line: &str) -> Option<OpenRegion<'a>> {
+        let trimmed = line.trim_start();
+
+        self.regions.iter().find_map(|region| {
+            if !starts_with_tag(trimmed, &region.start) {
+                return None;
+            }
+
+            // A region opened and closed on the same line stays with the parent.
+            let after_start = &trimmed[region.start.len()..];
+            if after_start.contains(region.end.as_str()) {
+                return None;
+            }
+
+            let requested = if region.info_string {
+                info_string_language(after_start)
+            } else {
+                region
+                    .attribute
+                    .as_deref()
+                    .and_then(|attribute| attribute_value(after_start, attribute))
+            };
+
+            let definition = requested
+                .and_then(LanguageRegistry::detect_by_name)
+                .or_else(|| {
+                    region
+                        .language
+                        .as_deref()
+                        .and_then(LanguageRegistry::get_language)
+                });
+
+            Some(OpenRegion {
+                region,
+                language: definition.map(|lang| lang.name.as_str()),
+                counter: LineCounter::new(definition),
+                depth: 0,
+            })
+        })
+    }
+
+    pub fn classify_line(&mut self, line: &str) -> LineKind {
+        match self.literate {
+            None => return self.classify_code(line),
+            Some(LiterateStyle::IndentedBlocks) => return self.classify_indented(line),
+            Some(LiterateStyle::BirdTracks) => {}
+        }
+
+        if line.trim().is_empty() {
+            return LineKind::Blank;
+        }
+
+        let trimmed = line.trim_start();
+
+        if self.in_code_block {
+            if trimmed.starts_with(LITERATE_CODE_END) {
+                self.in_code_block = false;
+                return LineKind::Comment;
+            }
+            return self.classify_code(line);
+        }
+
+        if trimmed.starts_with(LITERATE_CODE_BEGIN) {
+            self.in_code_block = true;
+            return LineKind::Comment;
+        }
+
+        // Bird tracks must sit in the first column; the marker itself is not code.
+        match line.strip_prefix('>') {
+            Some(code) if code.trim().is_empty() => LineKind::Blank,
+            Some(code) => self.classify_code(code),
+            None => LineKind::Comment,
+        }
+    }
+
+    // As in the CoffeeScript compiler, an indented line is code only after a
+    // blank line or more code; right after prose it continues the paragraph.
+    // `in_code_block` tracks whether code may follow.
+    fn classify_indented(&mut self, line: &str) -> LineKind {
+        if line.trim().is_empty() {
+            self.in_code_block = true;
+            return LineKind::Blank;
+        }
+
+        let spaces = line.len() - line.trim_start_matches(' ').len();
+        let indented = spaces >= 4 || line[spaces..].starts_with('\t');
+        if self.in_code_block && indented {
+            return self.classify_code(line);
+        }
+
+        self.in_code_block = false;
+        LineKind::Comment
+    }
+
+    fn classify_code(&mut self, line: &str) -> LineKind {
+        if line.trim().is_empty() {
+            return LineKind::Blank;
+        }
+
+        let mut has_code = matches!(self.state, State::String { doc: false, .. });
+        let mut has_doc = matches!(self.state, State::String { doc: true, .. })
+            || self.comment_stack.first().is_some_and(|token| token.doc);
+        let mut has_comment = self.state == State::Comment;
+        let mut i = 0;
+
+        while i < line.len() {
+            let rest = &line[i..];
+
+            match self.state {
+                State::String { end, verbatim, .. } => {
+                    if !verbatim && rest.starts_with('\\') {
+                        i += 1;
+                        i += line[i..].chars().next().map_or(0, char::len_utf8);
+                        continue;
+                    }
+                    if self.starts_with_marker(rest, end) {
+                        i += end.len();
+                        self.state = State::Code;
+                        continue;
+                    }
+                }
+                State::Comment => {
+                    if let Some(&current) = self.comment_stack.last() {
+                        if self.starts_with_marker(rest, current.end) {
+                            i += current.end.len();
+                            self.comment_stack.pop();
+                            if self.comment_stack.is_empty() {
+                                self.state = State::Code;
+                            }
+                            continue;
+                        }
+
+                        // Nested comments only open a new level with a marker that
+                        // shares their closing marker, so a `/*` inside `/+ ... +/`
+                        // is plain text.
+                        if current.nested {
+                            if let Some(&inner) = self.tokens.iter().find(|token| {
+                                token.kind == TokenKind::BlockComment
+                                    && token.end == current.end
+                                    && self.starts_with_marker(rest, token.start)
+                            }) {
+                                i += inner.start.len();
+                                self.comment_stack.push(inner);
+                                continue;
+                            }
+                        }
+                    }
+                }
+                State::Code => {
+                    if rest.starts_with(char::is_whitespace) {
+                        i += rest.chars().next().map_or(1, char::len_utf8);
+                        continue;
+                    }
+
+                    if let Some(token) = self.match_token(rest, i == 0, has_code) {
+                        i += token.start.len();
+                        match token.kind {
+                            TokenKind::LineComment => {
+                                has_comment = true;
+                                has_doc |= token.doc;
+                                break;
+                            }
+                            TokenKind::BlockComment => {
+                                has_comment = true;
+                                has_doc |= token.doc;
+                                self.comment_stack.push(token);
+                                self.state = State::Comment;
+                            }
+                            TokenKind::DocQuote if !has_code => {
+                                has_doc = true;
+                                self.state = State::String {
+                                    end: token.end,
+                                    verbatim: false,
+                                    doc: true,
+                                };
+                            }
+                            TokenKind::Quote | TokenKind::DocQuote | TokenKind::VerbatimQuote => {
+                                has_code = true;
+                                self.state = State::String {
+                                    end: token.end,
+                                    verbatim: token.kind == TokenKind::VerbatimQuote,
+                                    doc: false,
+                                };
+                            }
+                        }
+                        continue;
+                    }
+
+                    has_code = true;
+
+                    if self.char_literals {
+                        if let Some(len) = char_literal_len(rest) {
+                            i += len;
+                            continue;
+                        }
+                    }
+                }
+            }
+
+            i += rest.chars().next().map_or(1, char::len_utf8);
+        }
+
+        if has_code {
+            LineKind::Code
+        } else if has_doc {
+            LineKind::DocComment
+        } else if has_comment {
+            LineKind::Comment
+        } else {
+            LineKind::Code
+        }
+    }
+
+    fn match_token(&self, text: &str, line_start: bool, has_code: bool) -> Option<Token<'a>> {
+        self.tokens
+            .iter()
+            .find(|token| {
+                self.starts_with_marker(text, token.start)
+                    && !(token.kind == TokenKind::LineComment
+                        && self.line_comments_at_start
+                        && !line_start)
+                    && !(token.doc
+                        && token.kind != TokenKind::DocQuote
+                        && self.is_plain_comment(text, has_code))
+            })
+            .copied()
+    }
+
+    // Doc comment markers only count when they start the line, and the empty
+    // block `/**/` and the separator `////` are ordinary comments.
+    fn is_plain_comment(&self, text: &str, has_code: bool) -> bool {
+        if has_code {
+            return true;
+        }
+
+        self.tokens.iter().any(|token| {
+            !token.doc
+                && token.kind != TokenKind::Quote
+                && token.kind != TokenKind::VerbatimQuote
+                && self.starts_with_marker(text, token.start)
+                && self.starts_with_marker(
+                    &text[token.start.len()..],
+                    if token.end.is_empty() {
+                        token.start
+                    } else {
+                        token.end
+                    },
+                )
+        })
+    }
+
+    // Markers that are words, such as `REM`, only match as a whole word, so
+    // `Remove-Item` isn't a comment. Single letters are column markers, like
+    // the `c` of fixed-form Fortran, and match whatever follows them.
+    fn starts_with_marker(&self, text: &str, marker: &str) -> bool {
+        let matched = if self.case_sensitive {
+            text.starts_with(marker)
+        } else {
+            text.get(..marker.len())
+                .is_some_and(|prefix| prefix.eq_ignore_ascii_case(marker))
+        };
+        if !matched || marker.len() < 2 || !marker.ends_with(is_identifier_char) {
+            return matched;
+        }
+
+        !text[marker.len()..].starts_with(is_identifier_char)
+    }
+}
+
+const LITERATE_CODE_BEGIN: &str = "\\begin{code}";
+const LITERATE_CODE_END: &str = "\\end{code}";
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn starts_with_tag(text: &str, marker: &str) -> bool {
+    match text.strip_prefix(marker) {
+        Some(rest) => {
+            !marker.ends_with(|c: char| c.is_ascii_alphanumeric())
+                || !rest.starts_with(|c: char| c.is_ascii_alphanumeric() || c == '-')
+        }
+        None => false,
+    }
+}
+
+fn info_string_language(info: &str) -> Option<&str> {
+    info.trim()
+        .trim_start_matches(['{', '.'])
+        .split(|c: char| c.is_whitespace() || c == ',' || c == '}')
+        .next()
+        .filter(|name| !name.is_empty())
+}
+
+fn attribute_value<'t>(tag: &'t str, attribute: &str) -> Option<&'t str> {
+    let mut offset = 0;
+
+    while let Some(found) = tag[offset..].find(attribute) {
+        let start = offset + found;
+        offset = start + attribute.len();
+
+        if !tag[..start].ends_with(char::is_whitespace) {
+            continue;
+        }
+
+        let Some(value) = tag[offset..].trim_start().strip_prefix('=') else {
+            continue;
+        };
+        let value = value.trim_start();
+
+        return match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => value[1..].split(quote).next(),
+            _ => value
+                .split(|c: char| c.is_whitespace() || c == '>')
+                .next()
+                .filter(|v| !v.is_empty()),
+        };
+    }
+
+    None
+}
+
+fn char_literal_len(text: &str) -> Option<usize> {
+    let body = text.strip_prefix('\'')?;
+    let mut chars = body.char_indices();
+    let (_, first) = chars.next()?;
+
+    if first == '\\' {
+        // Escapes such as '\n', '\'' or '\u{1F600}'.
+        let (escaped_at, _) = chars.next()?;
+        let close = body[escaped_at + 1..].find('\'')?;
+        if close <= 10 {
+            return Some(1 + escaped_at + 1 + close + 1);
+        }
+        return None;
+    }
+
+    let (next_at, next) = chars.next()?;
+    if next == '\'' {
+        Some(1 + next_at + 1)
+    } else {
+        None
+    }
+}
@@ -61,6 +61,9 @@ pub enum AnalysisError {
 
     #[error("URL parsing error: {url}")]
     UrlParsingError { url: String },
+
+    #[error("Git repository error: {message}")]
+    GitError { message: String },
 }
 
 pub type Result<T> = std::result::Result<T, AnalysisError>;
@@ -139,4 +142,10 @@ impl AnalysisError {
             message: message.as_ref().to_string(),
         }
     }
+
+    pub fn git<M: AsRef<str>>(message: M) -> Self {
+        Self::GitError {
+            message: message.as_ref().to_string(),
+        }
+    }
 }
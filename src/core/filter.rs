@@ -1,31 +1,131 @@
+use super::attributes::LinguistAttributes;
+use super::generated::generated_by_name;
+use super::glob::path_match;
+use super::ignore::IgnoreRules;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
 use std::path::Path;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IntelligentFilter {
     pub max_file_size: u64,
     pub ignore_hidden: bool,
-    pub ignore_binary: bool,
     pub ignore_build_dirs: bool,
     pub ignore_package_dirs: bool,
     pub ignore_test_dirs: bool,
     pub ignore_docs_dirs: bool,
     pub custom_ignore_patterns: Vec<String>,
+    #[serde(default)]
+    pub include_patterns: Vec<String>,
     pub allowed_extensions: Option<Vec<String>>,
+    #[serde(default = "default_true")]
+    pub respect_bradarignore: bool,
+    #[serde(default)]
+    pub respect_gitignore: bool,
+    #[serde(default)]
+    pub explain: bool,
+    #[serde(skip)]
+    pub subpath: Option<String>,
+    #[serde(default)]
+    pub keep_archive_root: bool,
+    #[serde(default)]
+    pub count_generated: bool,
 }
 
+/// Why the filter kept or skipped an archive entry.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum FilterDecision {
+    Include,
+    Empty,
+    TooLarge,
+    Hidden,
+    Binary,
+    Generated(String),
+    BuildDir,
+    PackageDir,
+    TestDir,
+    DocsDir,
+    LinguistAttribute(String),
+    IgnoreFile(String),
+    CustomPattern(String),
+    NotIncluded,
+    ExtensionNotAllowed,
+}
+
+impl FilterDecision {
+    pub fn is_included(&self) -> bool {
+        matches!(self, FilterDecision::Include)
+    }
+
+    /// A stable label for the rule, without any pattern it carries.
+    pub fn reason(&self) -> &'static str {
+        match self {
+            FilterDecision::Include => "included",
+            FilterDecision::Empty => "empty",
+            FilterDecision::TooLarge => "too_large",
+            FilterDecision::Hidden => "hidden",
+            FilterDecision::Binary => "binary",
+            FilterDecision::Generated(_) => "generated",
+            FilterDecision::BuildDir => "build_dir",
+            FilterDecision::PackageDir => "package_dir",
+            FilterDecision::TestDir => "test_dir",
+            FilterDecision::DocsDir => "docs_dir",
+            FilterDecision::LinguistAttribute(_) => "linguist_attribute",
+            FilterDecision::IgnoreFile(_) => "ignore_file",
+            FilterDecision::CustomPattern(_) => "custom_pattern",
+            FilterDecision::NotIncluded => "not_included",
+            FilterDecision::ExtensionNotAllowed => "extension_not_allowed",
+        }
+    }
+}
+
+impl Display for FilterDecision {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FilterDecision::Generated(detail)
+            | FilterDecision::LinguistAttribute(detail)
+            | FilterDecision::IgnoreFile(detail)
+            | FilterDecision::CustomPattern(detail) => write!(f, "{} ({})", self.reason(), detail),
+            _ => write!(f, "{}", self.reason()),
+        }
+    }
+}
+
+/// The filter decision for a single archive entry, kept when
+/// [`IntelligentFilter::explain`] is set.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntryDecision {
+    pub path: String,
+    pub size_bytes: u64,
+    pub decision: FilterDecision,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+const BRADARIGNORE: &str = ".bradarignore";
+const GITIGNORE: &str = ".gitignore";
+
 impl Default for IntelligentFilter {
     fn default() -> Self {
         Self {
             max_file_size: 1024 * 1024,
             ignore_hidden: true,
-            ignore_binary: true,
             ignore_build_dirs: true,
             ignore_package_dirs: true,
             ignore_test_dirs: false,
             ignore_docs_dirs: false,
             custom_ignore_patterns: Vec::new(),
+            include_patterns: Vec::new(),
             allowed_extensions: None,
+            respect_bradarignore: true,
+            respect_gitignore: false,
+            explain: false,
+            subpath: None,
+            keep_archive_root: false,
+            count_generated: false,
         }
     }
 }
@@ -39,7 +139,6 @@ impl IntelligentFilter {
         Self {
             max_file_size: 512 * 1024,
             ignore_hidden: true,
-            ignore_binary: true,
             ignore_build_dirs: true,
             ignore_package_dirs: true,
             ignore_test_dirs: true,
@@ -51,6 +150,7 @@ impl IntelligentFilter {
                 "*.temp".to_string(),
                 "*.cache".to_string(),
             ],
+            include_patterns: Vec::new(),
             allowed_extensions: Some(vec![
                 "rs".to_string(),
                 "js".to_string(),
@@ -73,56 +173,167 @@ impl IntelligentFilter {
                 "jsx".to_string(),
                 "tsx".to_string(),
             ]),
+            respect_bradarignore: true,
+            respect_gitignore: false,
+            explain: false,
+            subpath: None,
+            keep_archive_root: false,
+            count_generated: false,
         }
     }
 
+    /// Whether `file_path`, relative to the repository root, is an ignore
+    /// file this filter should load: the root `.bradarignore`, or a
+    /// `.gitignore` at any depth when `respect_gitignore` is set.
+    pub fn is_ignore_file(&self, file_path: &str) -> bool {
+        match file_path.rsplit_once('/') {
+            Some((_, name)) => name == GITIGNORE && self.respect_gitignore,
+            None => match file_path {
+                BRADARIGNORE => self.respect_bradarignore,
+                GITIGNORE => self.respect_gitignore,
+                _ => false,
+            },
+        }
+    }
+
+    /// Whether `file_path`, relative to the repository root, lies inside
+    /// [`IntelligentFilter::subpath`].
+    pub fn is_within_subpath(&self, file_path: &str) -> bool {
+        let Some(subpath) = self.subpath.as_deref() else {
+            return true;
+        };
+
+        file_path
+            .strip_prefix(subpath)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    }
+
     pub fn should_process_file(&self, file_path: &str, file_size: u64) -> bool {
+        self.decide(file_path, file_size, &LinguistAttributes::default())
+            .is_included()
+    }
+
+    /// Like [`IntelligentFilter::should_process_file`], but lets explicit
+    /// `.gitattributes` linguist attributes override the directory heuristics.
+    pub fn should_process_file_with_attributes(
+        &self,
+        file_path: &str,
+        file_size: u64,
+        attributes: &LinguistAttributes,
+    ) -> bool {
+        self.decide(file_path, file_size, attributes).is_included()
+    }
+
+    /// Returns the first rule that rejects `file_path`, or
+    /// [`FilterDecision::Include`] when it should be analyzed.
+    pub fn decide(
+        &self,
+        file_path: &str,
+        file_size: u64,
+        attributes: &LinguistAttributes,
+    ) -> FilterDecision {
+        self.decide_with_ignore_rules(file_path, file_size, attributes, &IgnoreRules::new())
+    }
+
+    /// Like [`IntelligentFilter::decide`], but also applies the rules of the
+    /// ignore files found in the archive so far.
+    pub fn decide_with_ignore_rules(
+        &self,
+        file_path: &str,
+        file_size: u64,
+        attributes: &LinguistAttributes,
+        ignore_rules: &IgnoreRules,
+    ) -> FilterDecision {
         let path = Path::new(file_path);
 
-        if file_size > self.max_file_size {
-            return false;
+        // Counting generated files only lifts `linguist-generated`; vendored
+        // and documentation files stay excluded.
+        let excluded_by = if self.count_generated {
+            LinguistAttributes {
+                generated: None,
+                ..attributes.clone()
+            }
+            .excluded_by()
+        } else {
+            attributes.excluded_by()
+        };
+        match excluded_by {
+            // Generated files share one reason however they were recognized,
+            // so their lines still count towards the generated total.
+            Some(attribute @ "linguist-generated") => {
+                return FilterDecision::Generated(attribute.to_string());
+            }
+            Some(attribute) => return FilterDecision::LinguistAttribute(attribute.to_string()),
+            None => {}
         }
 
-        if self.ignore_hidden && self.is_hidden_file(path) {
-            return false;
+        // Checked before the size and directory rules, so that every binary
+        // file is reported by size wherever it is.
+        if self.is_binary_file(path) {
+            return FilterDecision::Binary;
         }
 
-        if self.ignore_binary && self.is_binary_file(path) {
-            return false;
+        if file_size > self.max_file_size {
+            return FilterDecision::TooLarge;
+        }
+
+        if self.ignore_hidden && self.is_hidden_file(path) {
+            return FilterDecision::Hidden;
         }
 
         if self.ignore_build_dirs && self.is_in_build_directory(path) {
-            return false;
+            return FilterDecision::BuildDir;
         }
 
-        if self.ignore_package_dirs && self.is_in_package_directory(path) {
-            return false;
+        if self.ignore_package_dirs
+            && attributes.vendored.is_none()
+            && self.is_in_package_directory(path)
+        {
+            return FilterDecision::PackageDir;
         }
 
         if self.ignore_test_dirs && self.is_in_test_directory(path) {
-            return false;
+            return FilterDecision::TestDir;
         }
 
-        if self.ignore_docs_dirs && self.is_in_docs_directory(path) {
-            return false;
+        if self.ignore_docs_dirs
+            && attributes.documentation.is_none()
+            && self.is_in_docs_directory(path)
+        {
+            return FilterDecision::DocsDir;
         }
 
-        if self.matches_custom_ignore_patterns(file_path) {
-            return false;
+        if let Some(pattern) = ignore_rules.ignored_by(file_path) {
+            return FilterDecision::IgnoreFile(pattern.to_string());
+        }
+
+        if let Some(pattern) = self.matching_custom_ignore_pattern(file_path) {
+            return FilterDecision::CustomPattern(pattern.to_string());
+        }
+
+        if !self.matches_include_patterns(file_path) {
+            return FilterDecision::NotIncluded;
         }
 
         if let Some(ref allowed_exts) = self.allowed_extensions {
-            if let Some(ext) = path.extension() {
+            let allowed = path.extension().is_some_and(|ext| {
                 let ext_str = ext.to_string_lossy().to_lowercase();
-                if !allowed_exts.iter().any(|e| e.to_lowercase() == ext_str) {
-                    return false;
-                }
-            } else {
-                return false;
+                allowed_exts.iter().any(|e| e.to_lowercase() == ext_str)
+            });
+            if !allowed {
+                return FilterDecision::ExtensionNotAllowed;
             }
         }
 
-        true
+        // Generated files are also recognized by their header once read, so
+        // only the naming conventions are checked here.
+        if !self.count_generated && attributes.generated.is_none() {
+            if let Some(pattern) = generated_by_name(file_path) {
+                return FilterDecision::Generated(pattern.to_string());
+            }
+        }
+
+        FilterDecision::Include
     }
 
     fn is_hidden_file(&self, path: &Path) -> bool {
@@ -240,36 +451,44 @@ impl IntelligentFilter {
         })
     }
 
-    fn matches_custom_ignore_patterns(&self, file_path: &str) -> bool {
-        for pattern in &self.custom_ignore_patterns {
-            if self.glob_match(pattern, file_path) {
-                return true;
-            }
-        }
-        false
+    fn matching_custom_ignore_pattern(&self, file_path: &str) -> Option<&str> {
+        self.custom_ignore_patterns
+            .iter()
+            .find(|pattern| path_match(pattern, file_path))
+            .map(String::as_str)
     }
 
-    fn glob_match(&self, pattern: &str, text: &str) -> bool {
-        if pattern.contains('*') {
-            let pattern_parts: Vec<&str> = pattern.split('*').collect();
-            if pattern_parts.len() == 2 {
-                let prefix = pattern_parts[0];
-                let suffix = pattern_parts[1];
-                text.starts_with(prefix) && text.ends_with(suffix)
-            } else {
-                false
-            }
-        } else {
-            text == pattern
-        }
+    fn matches_include_patterns(&self, file_path: &str) -> bool {
+        self.include_patterns.is_empty()
+            || self
+                .include_patterns
+                .iter()
+                .any(|pattern| path_match(pattern, file_path))
     }
 }
 
+/// Entries and bytes skipped for one filter reason.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReasonStats {
+    pub entries: usize,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FilterStats {
     pub total_entries: usize,
     pub filtered_out: usize,
     pub processed: usize,
     pub bytes_saved: u64,
+    #[serde(default)]
+    pub by_reason: BTreeMap<String, ReasonStats>,
+    /// Lines in the generated files that were left out.
+    #[serde(default)]
+    pub generated_lines: usize,
+    #[serde(skip)]
+    pub decisions: Vec<EntryDecision>,
+    #[serde(skip)]
+    explain: bool,
 }
 
 impl Default for FilterStats {
@@ -285,6 +504,39 @@ impl FilterStats {
             filtered_out: 0,
             processed: 0,
             bytes_saved: 0,
+            by_reason: BTreeMap::new(),
+            generated_lines: 0,
+            decisions: Vec::new(),
+            explain: false,
+        }
+    }
+
+    /// Stats that also keep every entry's decision, for `--explain-filter`.
+    pub fn for_filter(filter: &IntelligentFilter) -> Self {
+        Self {
+            explain: filter.explain,
+            ..Self::new()
+        }
+    }
+
+    pub fn record_decision(&mut self, file_path: &str, file_size: u64, decision: FilterDecision) {
+        self.record_entry(file_size, !decision.is_included());
+
+        if !decision.is_included() {
+            let reason = self
+                .by_reason
+                .entry(decision.reason().to_string())
+                .or_default();
+            reason.entries += 1;
+            reason.bytes += file_size;
+        }
+
+        if self.explain {
+            self.decisions.push(EntryDecision {
+                path: file_path.to_string(),
+                size_bytes: file_size,
+                decision,
+            });
         }
     }
 
@@ -298,6 +550,21 @@ impl FilterStats {
         }
     }
 
+    /// Adds the counters of `other`, e.g. from another archive.
+    pub fn merge(&mut self, other: &FilterStats) {
+        self.total_entries += other.total_entries;
+        self.filtered_out += other.filtered_out;
+        self.processed += other.processed;
+        self.bytes_saved += other.bytes_saved;
+        self.generated_lines += other.generated_lines;
+
+        for (reason, stats) in &other.by_reason {
+            let entry = self.by_reason.entry(reason.clone()).or_default();
+            entry.entries += stats.entries;
+            entry.bytes += stats.bytes;
+        }
+    }
+
     pub fn filter_ratio(&self) -> f64 {
         if self.total_entries == 0 {
             0.0
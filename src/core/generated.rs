@@ -0,0 +1,79 @@
+//! Detection of generated source files, by the naming conventions of code
+//! generators and by the "do not edit" headers they write.
+
+use super::attributes::LinguistAttributes;
+use super::glob::path_match;
+
+/// Output names of the protobuf, gRPC and Thrift code generators.
+const GENERATED_PATTERNS: &[&str] = &[
+    "*.pb.go",
+    "*.pb.gw.go",
+    "*_pb2.py",
+    "*_pb2.pyi",
+    "*_pb2_grpc.py",
+    "*.pb.cc",
+    "*.pb.h",
+    "*_pb.js",
+    "*_pb.d.ts",
+    "*_grpc_pb.js",
+    "*_grpc_pb.d.ts",
+    "*.pb.swift",
+    "*.grpc.swift",
+    "*.pb.dart",
+    "*.pbenum.dart",
+    "*.pbgrpc.dart",
+    "*.pbjson.dart",
+    "gen-*/",
+];
+
+// Generators put their header at the top of the file, sometimes below a
+// license block.
+const HEADER_LINES: usize = 30;
+
+/// Why a file counts as generated, if it does. An explicit
+/// `linguist-generated` attribute wins over both naming and content.
+pub fn generated_by(
+    path: &str,
+    content: &str,
+    attributes: &LinguistAttributes,
+) -> Option<&'static str> {
+    match attributes.generated {
+        Some(true) => Some("linguist-generated"),
+        Some(false) => None,
+        None => generated_by_name(path).or_else(|| generated_by_header(content)),
+    }
+}
+
+/// The generator naming convention `path` follows, if any.
+pub fn generated_by_name(path: &str) -> Option<&'static str> {
+    GENERATED_PATTERNS
+        .iter()
+        .copied()
+        .find(|pattern| path_match(pattern, path))
+}
+
+/// The generated-code marker in the leading comments of `content`, if any.
+pub fn generated_by_header(content: &str) -> Option<&'static str> {
+    content
+        .lines()
+        .take(HEADER_LINES)
+        .map(str::trim)
+        .filter(|line| is_comment(line))
+        .find_map(|line| {
+            if line.contains("@generated") {
+                Some("@generated")
+            } else if line.contains("<auto-generated") {
+                Some("<auto-generated>")
+            } else if line.contains("Code generated") && line.contains("DO NOT EDIT") {
+                Some("Code generated ... DO NOT EDIT.")
+            } else {
+                None
+            }
+        })
+}
+
+fn is_comment(line: &str) -> bool {
+    ["//", "/*", "*", "#", "--", ";", "<!--", "\"\"\"", "'"]
+        .iter()
+        .any(|prefix| line.starts_with(prefix))
+}
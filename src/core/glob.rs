@@ -0,0 +1,203 @@
+//! Path-aware glob matching shared by the filter and attribute files.
+//!
+//! `*` and `?` never cross a `/`, `**` matches any number of directories,
+//! `[...]` is a character class (`[!...]` or `[^...]` negates it) and
+//! `{a,b}` matches either alternative. A backslash escapes the next character.
+
+pub fn glob_match(pattern: &str, path: &str) -> bool {
+    let path: Vec<char> = path.chars().collect();
+
+    expand_braces(pattern).iter().any(|alternative| {
+        let alternative: Vec<char> = alternative.chars().collect();
+        match_from(&alternative, &path)
+    })
+}
+
+/// Matches a filter pattern against a path relative to the repository root,
+/// the way `.gitignore` patterns match. A leading `/` or a `/` in the middle
+/// anchors the pattern to the root; otherwise it matches the file name at any
+/// depth. A trailing `/` matches the files inside a matching directory.
+pub fn path_match(pattern: &str, path: &str) -> bool {
+    let (pattern, directory_only) = match pattern.strip_suffix('/') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+    let anchored = pattern.contains('/');
+    let pattern = pattern.strip_prefix('/').unwrap_or(pattern);
+
+    let matches = |candidate: &str| {
+        if anchored {
+            glob_match(pattern, candidate)
+        } else {
+            let name = candidate.rsplit('/').next().unwrap_or(candidate);
+            glob_match(pattern, name)
+        }
+    };
+
+    if directory_only {
+        path.match_indices('/')
+            .any(|(index, _)| matches(&path[..index]))
+    } else {
+        matches(path)
+    }
+}
+
+// Expands `{a,b}` alternation, including nested groups, into plain patterns.
+// Unbalanced braces are kept as literals.
+fn expand_braces(pattern: &str) -> Vec<String> {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut depth = 0;
+    let mut open = None;
+    let mut commas = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 1,
+            '{' => {
+                if depth == 0 {
+                    open = Some(i);
+                    commas.clear();
+                }
+                depth += 1;
+            }
+            ',' if depth == 1 => commas.push(i),
+            '}' if depth > 0 => {
+                depth -= 1;
+                if depth == 0 {
+                    let start = open.unwrap_or(0);
+                    if commas.is_empty() {
+                        // `{a}` is not an alternation; keep scanning past it.
+                        open = None;
+                        i += 1;
+                        continue;
+                    }
+
+                    let prefix: String = chars[..start].iter().collect();
+                    let suffix: String = chars[i + 1..].iter().collect();
+                    let mut bounds = vec![start];
+                    bounds.extend(&commas);
+                    bounds.push(i);
+
+                    return bounds
+                        .windows(2)
+                        .flat_map(|window| {
+                            let alternative: String =
+                                chars[window[0] + 1..window[1]].iter().collect();
+                            expand_braces(&format!("{}{}{}", prefix, alternative, suffix))
+                        })
+                        .collect();
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    vec![pattern.to_string()]
+}
+
+fn match_from(pattern: &[char], text: &[char]) -> bool {
+    let mut p = 0;
+    let mut t = 0;
+
+    while p < pattern.len() {
+        match pattern[p] {
+            '*' if pattern.get(p + 1) == Some(&'*') => {
+                let rest = &pattern[p + 2..];
+
+                // `**/` also matches zero directories.
+                if let Some(('/', after_slash)) = rest.split_first() {
+                    if match_from(after_slash, &text[t..]) {
+                        return true;
+                    }
+                    return (t..text.len())
+                        .filter(|&i| text[i] == '/')
+                        .any(|i| match_from(after_slash, &text[i + 1..]));
+                }
+
+                return (t..=text.len()).any(|i| match_from(rest, &text[i..]));
+            }
+            '*' => {
+                let rest = &pattern[p + 1..];
+                let segment_end = text[t..]
+                    .iter()
+                    .position(|&c| c == '/')
+                    .map_or(text.len(), |i| t + i);
+
+                return (t..=segment_end).any(|i| match_from(rest, &text[i..]));
+            }
+            '?' => {
+                if t >= text.len() || text[t] == '/' {
+                    return false;
+                }
+                p += 1;
+                t += 1;
+            }
+            '[' => match match_class(&pattern[p..], text.get(t).copied()) {
+                Some((true, len)) => {
+                    p += len;
+                    t += 1;
+                }
+                Some((false, _)) => return false,
+                // An unterminated class is a literal `[`.
+                None => {
+                    if text.get(t) != Some(&'[') {
+                        return false;
+                    }
+                    p += 1;
+                    t += 1;
+                }
+            },
+            '\\' if p + 1 < pattern.len() => {
+                if text.get(t) != Some(&pattern[p + 1]) {
+                    return false;
+                }
+                p += 2;
+                t += 1;
+            }
+            c => {
+                if text.get(t) != Some(&c) {
+                    return false;
+                }
+                p += 1;
+                t += 1;
+            }
+        }
+    }
+
+    t == text.len()
+}
+
+// Returns whether `c` matches the class at the start of `pattern` and the
+// length of the class, or `None` if the class is not terminated.
+fn match_class(pattern: &[char], c: Option<char>) -> Option<(bool, usize)> {
+    let mut i = 1;
+    let negated = matches!(pattern.get(i), Some('!') | Some('^'));
+    if negated {
+        i += 1;
+    }
+
+    let mut matched = false;
+    let mut first = true;
+
+    while i < pattern.len() {
+        let start = pattern[i];
+        if start == ']' && !first {
+            let matched = c.is_some_and(|c| c != '/') && matched != negated;
+            return Some((matched, i + 1));
+        }
+        first = false;
+
+        if pattern.get(i + 1) == Some(&'-') && pattern.get(i + 2).is_some_and(|&e| e != ']') {
+            let end = pattern[i + 2];
+            matched |= c.is_some_and(|c| start <= c && c <= end);
+            i += 3;
+        } else {
+            matched |= c == Some(start);
+            i += 1;
+        }
+    }
+
+    None
+}
@@ -0,0 +1,110 @@
+//! Content heuristics for extensions claimed by more than one language.
+//!
+//! Each extension lists its rules in priority order; the first rule with a
+//! matching pattern wins. When nothing matches, the extension's default is
+//! used and the file is reported as ambiguous unless that default is certain.
+
+const MAX_SCANNED_LINES: usize = 200;
+
+#[derive(Debug, Clone, Copy)]
+enum Pattern {
+    LineStartsWith(&'static str),
+    Contains(&'static str),
+}
+
+struct Rule {
+    language: &'static str,
+    patterns: &'static [Pattern],
+}
+
+struct Heuristic {
+    extension: &'static str,
+    default: &'static str,
+    // Set when every other candidate is reliably recognised by its rules, so
+    // an unmatched file is the default rather than ambiguous.
+    certain_default: bool,
+    rules: &'static [Rule],
+}
+
+use Pattern::{Contains, LineStartsWith};
+
+const HEURISTICS: &[Heuristic] = &[
+    Heuristic {
+        extension: "e",
+        default: "Phix",
+        certain_default: true,
+        rules: &[Rule {
+            language: "SpecmanE",
+            patterns: &[LineStartsWith("<'")],
+        }],
+    },
+    Heuristic {
+        extension: "m",
+        default: "ObjectiveC",
+        certain_default: false,
+        rules: &[
+            Rule {
+                language: "ObjectiveC",
+                patterns: &[
+                    LineStartsWith("#import"),
+                    LineStartsWith("#include"),
+                    LineStartsWith("@interface"),
+                    LineStartsWith("@implementation"),
+                    LineStartsWith("@end"),
+                ],
+            },
+            Rule {
+                language: "Matlab",
+                patterns: &[
+                    LineStartsWith("function "),
+                    LineStartsWith("%"),
+                    Contains("disp("),
+                    Contains("zeros("),
+                    Contains("fprintf("),
+                ],
+            },
+        ],
+    },
+];
+
+/// The language an ambiguous extension falls back to when no rule matches.
+pub(crate) fn default_language(extension: &str) -> Option<&'static str> {
+    HEURISTICS
+        .iter()
+        .find(|heuristic| heuristic.extension == extension)
+        .map(|heuristic| heuristic.default)
+}
+
+/// Runs the rules for `extension` over `content`, returning the first
+/// language that is also one of `candidates`, or a certain default.
+pub(crate) fn disambiguate(
+    extension: &str,
+    content: &str,
+    candidates: &[String],
+) -> Option<&'static str> {
+    let heuristic = HEURISTICS
+        .iter()
+        .find(|heuristic| heuristic.extension == extension)?;
+
+    heuristic
+        .rules
+        .iter()
+        .filter(|rule| candidates.iter().any(|c| c == rule.language))
+        .find(|rule| {
+            content
+                .lines()
+                .take(MAX_SCANNED_LINES)
+                .any(|line| rule.patterns.iter().any(|p| p.matches(line)))
+        })
+        .map(|rule| rule.language)
+        .or(heuristic.certain_default.then_some(heuristic.default))
+}
+
+impl Pattern {
+    fn matches(&self, line: &str) -> bool {
+        match self {
+            Pattern::LineStartsWith(prefix) => line.trim_start().starts_with(prefix),
+            Pattern::Contains(needle) => line.contains(needle),
+        }
+    }
+}
@@ -0,0 +1,146 @@
+//! Gitignore-style rules loaded from `.bradarignore` and `.gitignore` files
+//! found inside an archive.
+
+use super::glob::glob_match;
+
+#[derive(Debug, Clone)]
+struct IgnoreRule {
+    base: String,
+    depth: usize,
+    pattern: String,
+    negated: bool,
+    anchored: bool,
+    directory_only: bool,
+}
+
+/// Ignore rules collected from every ignore file seen so far. The last
+/// matching rule wins, and deeper files take precedence over shallower ones.
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreRules {
+    rules: Vec<IgnoreRule>,
+}
+
+impl IgnoreRules {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Adds the rules of the ignore file at `path`. Its patterns are relative
+    /// to the directory that contains it.
+    pub fn add_file(&mut self, path: &str, content: &str) {
+        let base = match path.rfind('/') {
+            Some(index) => &path[..=index],
+            None => "",
+        };
+        let depth = base.matches('/').count();
+
+        for line in content.lines() {
+            if let Some(rule) = parse_rule(base, depth, line) {
+                self.rules.push(rule);
+            }
+        }
+
+        self.rules.sort_by_key(|rule| rule.depth);
+    }
+
+    /// Whether the file at `path` is ignored. As in git, a file inside an
+    /// ignored directory cannot be re-included by a negated pattern.
+    pub fn is_ignored(&self, path: &str) -> bool {
+        self.ignored_by(path).is_some()
+    }
+
+    /// The pattern that ignores the file at `path`, if any.
+    pub fn ignored_by(&self, path: &str) -> Option<&str> {
+        if self.rules.is_empty() {
+            return None;
+        }
+
+        let directories = path.match_indices('/').map(|(index, _)| &path[..index]);
+
+        for directory in directories {
+            if let Some(rule) = self.matched(directory, true).filter(|rule| !rule.negated) {
+                return Some(&rule.pattern);
+            }
+        }
+
+        self.matched(path, false)
+            .filter(|rule| !rule.negated)
+            .map(|rule| rule.pattern.as_str())
+    }
+
+    fn matched(&self, path: &str, is_dir: bool) -> Option<&IgnoreRule> {
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| rule.matches(path, is_dir))
+    }
+}
+
+impl IgnoreRule {
+    fn matches(&self, path: &str, is_dir: bool) -> bool {
+        if self.directory_only && !is_dir {
+            return false;
+        }
+
+        let Some(relative) = path.strip_prefix(self.base.as_str()) else {
+            return false;
+        };
+        if relative.is_empty() {
+            return false;
+        }
+
+        if self.anchored {
+            glob_match(&self.pattern, relative)
+        } else {
+            let name = relative.rsplit('/').next().unwrap_or(relative);
+            glob_match(&self.pattern, name)
+        }
+    }
+}
+
+fn parse_rule(base: &str, depth: usize, line: &str) -> Option<IgnoreRule> {
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+
+    // Trailing spaces are ignored unless escaped with a backslash.
+    let mut line = line.trim_end_matches(['\r', '\n']);
+    while line.ends_with(' ') && !line.ends_with("\\ ") {
+        line = &line[..line.len() - 1];
+    }
+
+    let (negated, line) = match line.strip_prefix('!') {
+        Some(rest) => (true, rest),
+        None => (
+            false,
+            line.strip_prefix('\\')
+                .filter(|rest| rest.starts_with(['!', '#']))
+                .unwrap_or(line),
+        ),
+    };
+
+    let (directory_only, line) = match line.strip_suffix('/') {
+        Some(rest) => (true, rest),
+        None => (false, line),
+    };
+
+    // A slash anywhere but the end anchors the pattern to the ignore file.
+    let anchored = line.contains('/');
+    let pattern = line.strip_prefix('/').unwrap_or(line);
+    if pattern.is_empty() {
+        return None;
+    }
+
+    Some(IgnoreRule {
+        base: base.to_string(),
+        depth,
+        pattern: pattern.to_string(),
+        negated,
+        anchored,
+        directory_only,
+    })
+}
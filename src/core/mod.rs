@@ -1,8 +1,16 @@
 pub mod analysis;
+pub mod attributes;
+pub mod content;
+pub mod counter;
 pub mod error;
 pub mod filter;
+pub mod generated;
+pub mod glob;
+mod heuristics;
+pub mod ignore;
 pub mod registry;
 
 pub use analysis::*;
+pub use counter::*;
 pub use error::*;
 pub use registry::*;
@@ -4,6 +4,8 @@ use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
 use std::path::Path;
 
+use super::heuristics;
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub enum LanguageType {
     #[default]
@@ -35,6 +37,28 @@ pub enum LineCommentPosition {
     Start,
 }
 
+/// How a literate language marks its code among the prose.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub enum LiterateStyle {
+    /// `>` bird tracks and `\begin{code}` blocks, as in Literate Haskell.
+    #[default]
+    BirdTracks,
+    /// Markdown code blocks indented by four spaces or a tab.
+    IndentedBlocks,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddedRegion {
+    pub start: String,
+    pub end: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attribute: Option<String>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub info_string: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LanguageDefinition {
     #[serde(default)]
@@ -58,6 +82,10 @@ pub struct LanguageDefinition {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub nested_comments: Vec<(String, String)>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub doc_line_comments: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub doc_multi_line_comments: Vec<(String, String)>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub doc_quotes: Vec<(String, String)>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub quotes: Vec<(String, String)>,
@@ -65,9 +93,11 @@ pub struct LanguageDefinition {
     pub verbatim_quotes: Vec<(String, String)>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub important_syntax: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub embedded: Vec<EmbeddedRegion>,
     #[serde(default)]
     pub language_type: LanguageType,
-    #[serde(default, skip_serializing_if = "is_false")]
+    #[serde(default, skip_serializing_if = "is_false", alias = "literate")]
     pub is_literate: bool,
     #[serde(default, skip_serializing_if = "is_false", alias = "nested")]
     pub is_nested: bool,
@@ -77,6 +107,8 @@ pub struct LanguageDefinition {
     pub case_sensitive: bool,
     #[serde(default)]
     pub line_comment_position: LineCommentPosition,
+    #[serde(default)]
+    pub literate_style: LiterateStyle,
 }
 
 fn is_false(b: &bool) -> bool {
@@ -91,20 +123,44 @@ fn default_true() -> bool {
     true
 }
 
+/// Result of content-aware detection. `ambiguous_with` lists the other
+/// languages sharing the file's extension when no heuristic could decide.
+#[derive(Debug, Clone, Default)]
+pub struct LanguageDetection {
+    pub language: Option<&'static LanguageDefinition>,
+    pub ambiguous_with: Vec<&'static LanguageDefinition>,
+}
+
 pub struct LanguageRegistry;
 
 impl LanguageRegistry {
     pub fn get_language(name: &str) -> Option<&'static LanguageDefinition> {
-        LANGUAGE_MAP.get(name)
+        LANGUAGE_MAP
+            .get(name)
+            .or_else(|| NAME_MAP.get(name).and_then(|key| LANGUAGE_MAP.get(key)))
     }
 
     pub fn detect_by_extension(extension: &str) -> Option<&'static LanguageDefinition> {
         let ext = extension.to_lowercase();
         EXTENSION_MAP
             .get(&ext)
+            .and_then(|names| names.first())
             .and_then(|name| LANGUAGE_MAP.get(name))
     }
 
+    /// All languages claiming `extension`, with the default choice first.
+    pub fn extension_candidates(extension: &str) -> Vec<&'static LanguageDefinition> {
+        EXTENSION_MAP
+            .get(&extension.to_lowercase())
+            .map(|names| {
+                names
+                    .iter()
+                    .filter_map(|name| LANGUAGE_MAP.get(name))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
     pub fn detect_by_filename(filename: &str) -> Option<&'static LanguageDefinition> {
         let lower_filename = filename.to_lowercase();
         FILENAME_MAP
@@ -112,6 +168,16 @@ impl LanguageRegistry {
             .and_then(|name| LANGUAGE_MAP.get(name))
     }
 
+    pub fn detect_by_name(name: &str) -> Option<&'static LanguageDefinition> {
+        Self::get_language(name)
+            .or_else(|| {
+                LOWERCASE_NAME_MAP
+                    .get(&name.to_lowercase())
+                    .and_then(|key| LANGUAGE_MAP.get(key))
+            })
+            .or_else(|| Self::detect_by_extension(name))
+    }
+
     pub fn detect_by_path<P: AsRef<Path>>(path: P) -> Option<&'static LanguageDefinition> {
         let path = path.as_ref();
 
@@ -128,6 +194,145 @@ impl LanguageRegistry {
         None
     }
 
+    /// Detects a language from the file content and path. A vim or emacs
+    /// modeline wins over the path, and the interpreter named on a leading `#!`
+    /// line is used when the path is not recognised, so extensionless scripts
+    /// such as `bin/deploy` are still detected.
+    pub fn detect_by_content<P: AsRef<Path>>(
+        path: P,
+        first_bytes: &[u8],
+    ) -> Option<&'static LanguageDefinition> {
+        Self::detect(path, first_bytes).language
+    }
+
+    /// Like [`LanguageRegistry::detect_by_content`], but resolves extensions
+    /// claimed by several languages with content heuristics and reports the
+    /// remaining candidates when none of them apply.
+    pub fn detect<P: AsRef<Path>>(path: P, content: &[u8]) -> LanguageDetection {
+        let path = path.as_ref();
+
+        let known = Self::detect_by_modeline(content).or_else(|| {
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .and_then(Self::detect_by_filename)
+        });
+        if known.is_some() {
+            return LanguageDetection {
+                language: known,
+                ambiguous_with: Vec::new(),
+            };
+        }
+
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_lowercase);
+        let candidates = extension
+            .as_deref()
+            .and_then(|ext| EXTENSION_MAP.get(ext))
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+
+        match candidates {
+            [] => LanguageDetection {
+                language: Self::detect_by_shebang(content),
+                ambiguous_with: Vec::new(),
+            },
+            [single] => LanguageDetection {
+                language: LANGUAGE_MAP.get(single),
+                ambiguous_with: Vec::new(),
+            },
+            [default, ..] => {
+                let shebang = Self::detect_by_shebang(content).filter(|lang| {
+                    candidates
+                        .iter()
+                        .filter_map(|name| LANGUAGE_MAP.get(name))
+                        .any(|candidate| std::ptr::eq(candidate, *lang))
+                });
+                let decided = shebang.or_else(|| {
+                    let text = String::from_utf8_lossy(content);
+                    heuristics::disambiguate(extension.as_deref()?, &text, candidates)
+                        .and_then(|key| LANGUAGE_MAP.get(key))
+                });
+
+                match decided {
+                    Some(language) => LanguageDetection {
+                        language: Some(language),
+                        ambiguous_with: Vec::new(),
+                    },
+                    None => LanguageDetection {
+                        language: LANGUAGE_MAP.get(default),
+                        ambiguous_with: candidates[1..]
+                            .iter()
+                            .filter_map(|name| LANGUAGE_MAP.get(name))
+                            .collect(),
+                    },
+                }
+            }
+        }
+    }
+
+    /// Looks for `vim: set ft=ruby:` or `-*- mode: python -*-` modelines in the
+    /// first and last few lines of the content.
+    pub fn detect_by_modeline(content: &[u8]) -> Option<&'static LanguageDefinition> {
+        let content = String::from_utf8_lossy(content);
+
+        content
+            .lines()
+            .take(MODELINE_SCAN_LINES)
+            .chain(content.lines().rev().take(MODELINE_SCAN_LINES))
+            .find_map(|line| parse_vim_modeline(line).or_else(|| parse_emacs_modeline(line)))
+            .and_then(Self::detect_by_modeline_name)
+    }
+
+    fn detect_by_modeline_name(name: &str) -> Option<&'static LanguageDefinition> {
+        let name = name.to_lowercase();
+
+        MODELINE_ALIASES
+            .iter()
+            .find(|(alias, _)| *alias == name)
+            .and_then(|(_, key)| LANGUAGE_MAP.get(*key))
+            .or_else(|| Self::detect_by_name(&name))
+    }
+
+    pub fn detect_by_shebang(first_bytes: &[u8]) -> Option<&'static LanguageDefinition> {
+        let first_bytes = first_bytes
+            .strip_prefix(b"\xEF\xBB\xBF")
+            .unwrap_or(first_bytes);
+        let end = first_bytes
+            .iter()
+            .position(|&b| b == b'\n')
+            .unwrap_or(first_bytes.len());
+        let line = std::str::from_utf8(&first_bytes[..end]).ok()?.trim_end();
+
+        let command = line.strip_prefix("#!")?.trim();
+
+        if let Some(lang) = SHEBANG_MAP
+            .get(&format!("#!{}", command))
+            .and_then(|key| LANGUAGE_MAP.get(key))
+        {
+            return Some(lang);
+        }
+
+        let mut args = command.split_whitespace();
+        let mut interpreter = interpreter_name(args.next()?);
+
+        // `#!/usr/bin/env [-S] [NAME=value ...] node --flag`
+        if interpreter == "env" {
+            interpreter = args
+                .find(|arg| !arg.starts_with('-') && !arg.contains('='))
+                .map(interpreter_name)?;
+        }
+
+        ENV_MAP
+            .get(interpreter)
+            .or_else(|| {
+                // Versioned interpreters such as `python3.12` or `ruby2.7`.
+                ENV_MAP.get(interpreter.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.'))
+            })
+            .and_then(|key| LANGUAGE_MAP.get(key))
+    }
+
     pub fn all_languages() -> impl Iterator<Item = &'static LanguageDefinition> {
         LANGUAGE_MAP.values()
     }
@@ -150,16 +355,202 @@ fn create_languages() -> HashMap<String, LanguageDefinition> {
         if lang_def.name.is_empty() {
             lang_def.name = key.clone();
         }
+        unescape_syntax(lang_def);
     }
 
     languages
 }
 
-fn create_extension_map() -> HashMap<String, String> {
-    let mut map = HashMap::new();
+// languages.json stores comment and quote markers as escaped string literals
+// (e.g. `\"` for a double quote), so they are unescaped once at load time.
+fn unescape_syntax(lang_def: &mut LanguageDefinition) {
+    for markers in [&mut lang_def.line_comments, &mut lang_def.doc_line_comments] {
+        for marker in markers.iter_mut() {
+            *marker = unescape_marker(marker);
+        }
+    }
+
+    for pairs in [
+        &mut lang_def.multi_line_comments,
+        &mut lang_def.nested_comments,
+        &mut lang_def.doc_multi_line_comments,
+        &mut lang_def.doc_quotes,
+        &mut lang_def.quotes,
+        &mut lang_def.verbatim_quotes,
+    ] {
+        for (start, end) in pairs.iter_mut() {
+            *start = unescape_marker(start);
+            *end = unescape_marker(end);
+        }
+    }
+}
+
+fn unescape_marker(marker: &str) -> String {
+    let mut result = String::with_capacity(marker.len());
+    let mut chars = marker.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            if let Some(next) = chars.next() {
+                result.push(next);
+                continue;
+            }
+        }
+        result.push(c);
+    }
+
+    result
+}
+
+// Extensions claimed by several languages keep every candidate, sorted so the
+// result never depends on HashMap iteration order. The heuristic default, or
+// else the alphabetically first key, comes first.
+fn create_extension_map() -> HashMap<String, Vec<String>> {
+    let mut map: HashMap<String, Vec<String>> = HashMap::new();
     for (name, lang) in LANGUAGE_MAP.iter() {
         for ext in &lang.extensions {
-            map.insert(ext.clone(), name.clone());
+            map.entry(ext.to_lowercase())
+                .or_default()
+                .push(name.clone());
+        }
+    }
+
+    for (ext, names) in map.iter_mut() {
+        names.sort();
+        names.dedup();
+
+        if let Some(default) = heuristics::default_language(ext) {
+            if let Some(index) = names.iter().position(|name| name == default) {
+                let name = names.remove(index);
+                names.insert(0, name);
+            }
+        }
+    }
+
+    map
+}
+
+fn create_name_map() -> HashMap<String, String> {
+    LANGUAGE_MAP
+        .iter()
+        .map(|(key, lang)| (lang.name.clone(), key.clone()))
+        .collect()
+}
+
+fn create_lowercase_name_map() -> HashMap<String, String> {
+    let mut map = HashMap::new();
+    for (key, lang) in LANGUAGE_MAP.iter() {
+        map.insert(key.to_lowercase(), key.clone());
+        map.insert(lang.name.to_lowercase(), key.clone());
+    }
+    map
+}
+
+const MODELINE_SCAN_LINES: usize = 5;
+
+// Modeline names that differ from a languages.json key or display name. Names
+// that already match one case-insensitively resolve without an entry here.
+const MODELINE_ALIASES: &[(&str, &str)] = &[
+    ("csh", "CShell"),
+    ("tcsh", "CShell"),
+    ("python2", "Python"),
+    ("python3", "Python"),
+    ("perl6", "Raku"),
+    ("js", "JavaScript"),
+    ("js2", "JavaScript"),
+    ("javascriptreact", "Jsx"),
+    ("typescriptreact", "Tsx"),
+    ("cs", "CSharp"),
+    ("objc", "ObjectiveC"),
+    ("objcpp", "ObjectiveCpp"),
+    ("make", "Makefile"),
+    ("gmake", "Makefile"),
+    ("scss", "Sass"),
+    ("plaintex", "Tex"),
+    ("latex", "Tex"),
+    ("vim", "VimScript"),
+    ("emacs-lisp", "Elisp"),
+    ("lisp-interaction", "Elisp"),
+    ("lhaskell", "LiterateHaskell"),
+    ("literate-haskell", "LiterateHaskell"),
+    ("tuareg", "OCaml"),
+    ("dosbatch", "Batch"),
+    ("nasm", "Assembly"),
+    ("dosini", "Ini"),
+    ("fortran", "FortranModern"),
+    ("terraform", "Hcl"),
+];
+
+fn parse_vim_modeline(line: &str) -> Option<&str> {
+    let start = ["vim:", "vi:", "ex:"].iter().find_map(|marker| {
+        line.match_indices(marker)
+            .find(|(index, _)| *index == 0 || line[..*index].ends_with(char::is_whitespace))
+            .map(|(index, _)| index + marker.len())
+    })?;
+
+    let options = line[start..].trim_start();
+    let options = options
+        .strip_prefix("set ")
+        .or_else(|| options.strip_prefix("se "))
+        .unwrap_or(options);
+
+    options
+        .split(|c: char| c == ':' || c.is_whitespace())
+        .find_map(|option| {
+            let (key, value) = option.split_once('=')?;
+            matches!(key, "ft" | "filetype" | "syntax" | "syn")
+                .then_some(value)
+                .filter(|value| !value.is_empty())
+        })
+}
+
+fn parse_emacs_modeline(line: &str) -> Option<&str> {
+    let (_, rest) = line.split_once("-*-")?;
+    let (variables, _) = rest.split_once("-*-")?;
+
+    if !variables.contains(':') {
+        return Some(variables.trim()).filter(|mode| !mode.is_empty());
+    }
+
+    variables.split(';').find_map(|variable| {
+        let (key, value) = variable.split_once(':')?;
+        (key.trim().eq_ignore_ascii_case("mode"))
+            .then(|| value.trim())
+            .filter(|mode| !mode.is_empty())
+    })
+}
+
+fn interpreter_name(command: &str) -> &str {
+    command.rsplit('/').next().unwrap_or(command)
+}
+
+fn create_shebang_map() -> HashMap<String, String> {
+    let mut map = HashMap::new();
+    for (name, lang) in LANGUAGE_MAP.iter() {
+        for shebang in &lang.shebangs {
+            map.insert(shebang.clone(), name.clone());
+        }
+    }
+    map
+}
+
+fn create_env_map() -> HashMap<String, String> {
+    let mut map = HashMap::new();
+    for (name, lang) in LANGUAGE_MAP.iter() {
+        for interpreter in &lang.env {
+            map.insert(interpreter.clone(), name.clone());
+        }
+        for shebang in &lang.shebangs {
+            if let Some(command) = shebang
+                .strip_prefix("#!")
+                .and_then(|command| command.split_whitespace().next())
+            {
+                let interpreter = interpreter_name(command);
+                if interpreter != "env" {
+                    map.entry(interpreter.to_string())
+                        .or_insert_with(|| name.clone());
+                }
+            }
         }
     }
     map
@@ -176,5 +567,9 @@ fn create_filename_map() -> HashMap<String, String> {
 }
 
 static LANGUAGE_MAP: Lazy<HashMap<String, LanguageDefinition>> = Lazy::new(create_languages);
-static EXTENSION_MAP: Lazy<HashMap<String, String>> = Lazy::new(create_extension_map);
+static NAME_MAP: Lazy<HashMap<String, String>> = Lazy::new(create_name_map);
+static LOWERCASE_NAME_MAP: Lazy<HashMap<String, String>> = Lazy::new(create_lowercase_name_map);
+static EXTENSION_MAP: Lazy<HashMap<String, Vec<String>>> = Lazy::new(create_extension_map);
 static FILENAME_MAP: Lazy<HashMap<String, String>> = Lazy::new(create_filename_map);
+static SHEBANG_MAP: Lazy<HashMap<String, String>> = Lazy::new(create_shebang_map);
+static ENV_MAP: Lazy<HashMap<String, String>> = Lazy::new(create_env_map);
@@ -0,0 +1,712 @@
+//! Reading source files out of archives and local directories.
+//!
+//! Every container format, and the directory and git tree walkers, feeds its
+//! entries through the same [`EntryProcessor`], so filtering, `.gitattributes`
+//! handling and language detection behave identically whatever the source.
+
+use super::git::{GitRepository, ObjectId, TreeEntry};
+use crate::core::{
+    analysis::FileMetrics,
+    attributes::GitAttributes,
+    content::{decode_text, is_binary_content, SNIFF_LEN},
+    counter::LineCounter,
+    error::{AnalysisError, Result},
+    filter::{FilterDecision, FilterStats, IntelligentFilter},
+    generated::generated_by,
+    ignore::IgnoreRules,
+    registry::LanguageRegistry,
+};
+use bzip2_rs::DecoderReader as BzDecoder;
+use flate2::read::GzDecoder;
+use lzma_rust2::XzReader;
+use ruzstd::decoding::StreamingDecoder as ZstdDecoder;
+use std::fs::{self, File};
+use std::io::{BufReader, Cursor, Read, Seek};
+use std::path::Path;
+use tar::Archive;
+use zip::ZipArchive;
+
+/// File name suffixes of URLs that point straight at an archive. They only
+/// decide whether a URL is downloaded as-is; the format itself is detected
+/// from the downloaded bytes.
+pub const ARCHIVE_SUFFIXES: &[&str] = &[
+    ".tar.gz", ".tgz", ".tar.bz2", ".tbz2", ".tar.xz", ".txz", ".tar.zst", ".tzst", ".tar", ".zip",
+];
+
+/// Archive container formats, recognised by their leading magic bytes rather
+/// than by the URL they were downloaded from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    TarGz,
+    TarBz2,
+    TarXz,
+    TarZst,
+    Tar,
+    Zip,
+}
+
+impl ArchiveFormat {
+    /// Number of leading bytes [`ArchiveFormat::detect`] looks at, enough to
+    /// reach the `ustar` magic of an uncompressed tar header.
+    pub const MAGIC_LEN: usize = 262;
+
+    pub fn detect(header: &[u8]) -> Option<Self> {
+        if header.starts_with(&[0x1f, 0x8b]) {
+            Some(ArchiveFormat::TarGz)
+        } else if header.starts_with(b"BZh") {
+            Some(ArchiveFormat::TarBz2)
+        } else if header.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+            Some(ArchiveFormat::TarXz)
+        } else if header.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Some(ArchiveFormat::TarZst)
+        } else if header.starts_with(b"PK\x03\x04") || header.starts_with(b"PK\x05\x06") {
+            // The second signature is an empty archive's end-of-directory.
+            Some(ArchiveFormat::Zip)
+        } else if header.get(257..262) == Some(b"ustar") {
+            // Both POSIX (`ustar\0`) and GNU (`ustar `) headers.
+            Some(ArchiveFormat::Tar)
+        } else {
+            None
+        }
+    }
+}
+
+/// What an archive yielded, with paths relative to its root directory unless
+/// [`IntelligentFilter::keep_archive_root`] is set.
+pub(crate) struct ArchiveResults {
+    pub metrics: Vec<FileMetrics>,
+    pub stats: FilterStats,
+    pub root: Option<String>,
+}
+
+/// Analyzes an archive held in memory. ZIP archives are read through their
+/// central directory.
+pub(crate) fn analyze_buffered_sync(
+    bytes: bytes::Bytes,
+    filter: &IntelligentFilter,
+) -> Result<ArchiveResults> {
+    match ArchiveFormat::detect(&bytes) {
+        Some(ArchiveFormat::Zip) => analyze_zip_sync(Cursor::new(bytes), filter),
+        _ => analyze_stream_sync(Cursor::new(bytes), filter),
+    }
+}
+
+/// Analyzes an archive while it is being read. ZIP archives are read through
+/// the local header in front of each entry, since the central directory is
+/// only available once the whole archive has arrived.
+pub(crate) fn analyze_stream_sync<R: Read>(
+    mut reader: R,
+    filter: &IntelligentFilter,
+) -> Result<ArchiveResults> {
+    let mut header = Vec::with_capacity(ArchiveFormat::MAGIC_LEN);
+    (&mut reader)
+        .take(ArchiveFormat::MAGIC_LEN as u64)
+        .read_to_end(&mut header)
+        .map_err(|e| AnalysisError::archive(format!("Failed to read archive header: {}", e)))?;
+
+    let format = ArchiveFormat::detect(&header);
+    let reader = Cursor::new(header).chain(reader);
+
+    match format {
+        Some(ArchiveFormat::TarGz) => analyze_tar_sync(GzDecoder::new(reader), filter),
+        Some(ArchiveFormat::TarBz2) => analyze_tar_sync(BzDecoder::new(reader), filter),
+        Some(ArchiveFormat::TarXz) => analyze_tar_sync(XzReader::new(reader, true), filter),
+        Some(ArchiveFormat::TarZst) => {
+            // Only the first frame is decoded; `zstd` writes a single frame
+            // unless it is asked to split its output.
+            let decoder = ZstdDecoder::new(reader)
+                .map_err(|e| AnalysisError::archive(format!("Failed to read zstd frame: {}", e)))?;
+            analyze_tar_sync(decoder, filter)
+        }
+        Some(ArchiveFormat::Tar) => analyze_tar_sync(reader, filter),
+        Some(ArchiveFormat::Zip) => analyze_zip_stream_sync(reader, filter),
+        None => Err(AnalysisError::archive("Unrecognized archive format")),
+    }
+}
+
+/// Analyzes an archive file on disk. Like buffered archives, ZIP files are
+/// read through their central directory.
+pub(crate) fn analyze_file_sync(path: &Path, filter: &IntelligentFilter) -> Result<ArchiveResults> {
+    let display = path.to_string_lossy();
+    let mut file = File::open(path).map_err(|e| AnalysisError::file_read(&display, e))?;
+
+    let mut header = Vec::with_capacity(ArchiveFormat::MAGIC_LEN);
+    (&mut file)
+        .take(ArchiveFormat::MAGIC_LEN as u64)
+        .read_to_end(&mut header)
+        .and_then(|_| file.rewind())
+        .map_err(|e| AnalysisError::file_read(&display, e))?;
+
+    let reader = BufReader::new(file);
+    match ArchiveFormat::detect(&header) {
+        Some(ArchiveFormat::Zip) => analyze_zip_sync(reader, filter),
+        _ => analyze_stream_sync(reader, filter),
+    }
+}
+
+/// Analyzes a directory on disk as though it were an unpacked archive, with
+/// paths relative to `root`. Symbolic links are skipped, since archives carry
+/// them without content, and so is the `.git` directory.
+pub(crate) fn analyze_directory_sync(
+    root: &Path,
+    filter: &IntelligentFilter,
+) -> Result<ArchiveResults> {
+    let mut processor = EntryProcessor::with_root(filter, ArchiveRoot::none());
+    walk_directory(root, "", &mut processor)?;
+    processor.finish()
+}
+
+/// Analyzes the tree of a commit straight from a repository's object
+/// database, with paths relative to the repository root.
+pub(crate) fn analyze_git_sync(
+    git_dir: &Path,
+    rev: &str,
+    filter: &IntelligentFilter,
+) -> Result<ArchiveResults> {
+    let mut repository = GitRepository::open(git_dir)?;
+    let commit = repository.resolve(rev)?;
+    let tree = repository.commit_tree(commit)?;
+
+    let mut processor = EntryProcessor::with_root(filter, ArchiveRoot::none());
+    walk_tree(&mut repository, tree, "", &mut processor)?;
+    processor.finish()
+}
+
+fn walk_tree(
+    repository: &mut GitRepository,
+    tree: ObjectId,
+    prefix: &str,
+    processor: &mut EntryProcessor,
+) -> Result<()> {
+    let mut entries: Vec<TreeEntry> = repository.read_tree(tree)?;
+    entries.sort_by_cached_key(|entry| {
+        let path = format!("{}{}", prefix, entry.name);
+        (!processor.is_rules_file(&path), entry.name.clone())
+    });
+
+    for entry in entries {
+        let file_path = format!("{}{}", prefix, entry.name);
+
+        if entry.is_tree() {
+            walk_tree(repository, entry.id, &format!("{}/", file_path), processor)?;
+        } else if entry.is_file() {
+            let file_size = repository.object_size(entry.id)?;
+            let blob = LazyBlob {
+                repository,
+                id: entry.id,
+                content: None,
+            };
+            processor.process(&file_path, file_size, blob);
+        }
+    }
+
+    Ok(())
+}
+
+/// A blob that is only inflated once something reads it, so entries the
+/// filter rejects by path or size are never decompressed.
+struct LazyBlob<'a> {
+    repository: &'a mut GitRepository,
+    id: ObjectId,
+    content: Option<Cursor<Vec<u8>>>,
+}
+
+impl Read for LazyBlob<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.content.is_none() {
+            let blob = self
+                .repository
+                .read_blob(self.id)
+                .map_err(std::io::Error::other)?;
+            self.content = Some(Cursor::new(blob));
+        }
+
+        match &mut self.content {
+            Some(content) => content.read(buf),
+            None => Ok(0),
+        }
+    }
+}
+
+fn walk_directory(dir: &Path, prefix: &str, processor: &mut EntryProcessor) -> Result<()> {
+    let traversal_error = |e| AnalysisError::directory_traversal(dir.to_string_lossy(), e);
+
+    let mut entries = fs::read_dir(dir)
+        .and_then(|entries| entries.collect::<std::io::Result<Vec<_>>>())
+        .map_err(traversal_error)?;
+
+    // Attribute and ignore files come first so they apply to their siblings,
+    // as they do in archives written by `git archive`.
+    entries.sort_by_cached_key(|entry| {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let path = format!("{}{}", prefix, name);
+        (!processor.is_rules_file(&path), name)
+    });
+
+    for entry in entries {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let file_path = format!("{}{}", prefix, name);
+        let file_type = entry.file_type().map_err(traversal_error)?;
+
+        if file_type.is_dir() {
+            if name != ".git" {
+                walk_directory(&entry.path(), &format!("{}/", file_path), processor)?;
+            }
+        } else if file_type.is_file() {
+            let Ok(file) = File::open(entry.path()) else {
+                continue;
+            };
+            let file_size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            processor.process(&file_path, file_size, BufReader::new(file));
+        }
+    }
+
+    Ok(())
+}
+
+fn analyze_tar_sync<R: Read>(reader: R, filter: &IntelligentFilter) -> Result<ArchiveResults> {
+    let mut archive = Archive::new(reader);
+    let mut processor = EntryProcessor::new(filter);
+
+    let entries = archive
+        .entries()
+        .map_err(|e| AnalysisError::archive(format!("Failed to read tar entries: {}", e)))?;
+
+    for entry in entries {
+        let entry = entry
+            .map_err(|e| AnalysisError::archive(format!("Failed to read tar entry: {}", e)))?;
+
+        // GitHub stores the commit SHA in a pax global header ahead of the
+        // wrapper directory; it isn't part of the tree.
+        let entry_type = entry.header().entry_type();
+        if entry_type.is_pax_global_extensions() {
+            continue;
+        }
+
+        let Ok(path) = entry.path() else {
+            continue;
+        };
+        let file_path = path.to_string_lossy().to_string();
+        processor.observe(&file_path, entry_type.is_dir());
+
+        if entry_type.is_file() {
+            let file_size = entry.header().size().unwrap_or(0);
+            processor.process(&file_path, file_size, entry);
+        }
+    }
+
+    processor.finish()
+}
+
+fn analyze_zip_sync<R: Read + Seek>(
+    reader: R,
+    filter: &IntelligentFilter,
+) -> Result<ArchiveResults> {
+    let mut archive = ZipArchive::new(reader)
+        .map_err(|e| AnalysisError::archive(format!("Failed to read zip archive: {}", e)))?;
+    // The central directory lists every entry, so the root is known exactly.
+    let root = ArchiveRoot::scan(archive.file_names());
+    let mut processor = EntryProcessor::with_root(filter, root);
+
+    for index in 0..archive.len() {
+        let file = archive
+            .by_index(index)
+            .map_err(|e| AnalysisError::archive(format!("Failed to read zip entry: {}", e)))?;
+
+        let file_path = file.name().to_string();
+        if file.is_file() {
+            let file_size = file.size();
+            processor.process(&file_path, file_size, file);
+        }
+    }
+
+    processor.finish()
+}
+
+fn analyze_zip_stream_sync<R: Read>(
+    mut reader: R,
+    filter: &IntelligentFilter,
+) -> Result<ArchiveResults> {
+    let mut processor = EntryProcessor::new(filter);
+
+    // Entries written with a trailing data descriptor have no size in their
+    // local header and can't be streamed; the zip crate reports them as errors.
+    while let Some(file) = zip::read::read_zipfile_from_stream(&mut reader)
+        .map_err(|e| AnalysisError::archive(format!("Failed to read zip entry: {}", e)))?
+    {
+        let file_path = file.name().to_string();
+        processor.observe(&file_path, file.is_dir());
+
+        if file.is_file() {
+            let file_size = file.size();
+            processor.process(&file_path, file_size, file);
+        }
+    }
+
+    processor.finish()
+}
+
+/// The single top-level directory shared by every archive entry, such as
+/// the `repo-<sha>/` wrapper of forge tarballs.
+enum ArchiveRoot {
+    /// No entry has been seen yet.
+    Unknown,
+    /// Every entry so far lies in this directory, but a later one may not.
+    Guessed(String),
+    /// The root is certain, or there is none.
+    Known(Option<String>),
+}
+
+impl ArchiveRoot {
+    /// A root that is guessed from the entries as they are observed.
+    fn new() -> Self {
+        Self::Unknown
+    }
+
+    /// No root at all, for directory and git trees whose paths are already
+    /// relative to the repository root.
+    fn none() -> Self {
+        Self::Known(None)
+    }
+
+    /// The root shared by all of `paths`, for archives whose entries are all
+    /// known up front.
+    fn scan<'a>(paths: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut root: Option<&str> = None;
+        for path in paths {
+            match top_directory(path, path.ends_with('/')) {
+                Some(top) if root.is_none_or(|root| root == top) => root = Some(top),
+                _ => return Self::none(),
+            }
+        }
+
+        Self::Known(root.map(str::to_string))
+    }
+
+    /// Records an entry, which may settle the root: a top-level file or an
+    /// entry outside the guessed directory means there is none.
+    fn observe(&mut self, path: &str, is_dir: bool) {
+        let top = top_directory(path, is_dir);
+        match self {
+            Self::Unknown => {
+                *self = match top {
+                    Some(top) => Self::Guessed(top.to_string()),
+                    None => Self::none(),
+                }
+            }
+            Self::Guessed(root) if top != Some(root.as_str()) => *self = Self::none(),
+            _ => {}
+        }
+    }
+
+    /// Takes the guess as the root, once no entry is left to disprove it.
+    fn settle(&mut self) {
+        match self {
+            Self::Unknown => *self = Self::none(),
+            Self::Guessed(root) => *self = Self::Known(Some(std::mem::take(root))),
+            Self::Known(_) => {}
+        }
+    }
+
+    fn is_known(&self) -> bool {
+        matches!(self, Self::Known(_))
+    }
+
+    fn root(&self) -> Option<&str> {
+        match self {
+            Self::Known(root) => root.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// `path` relative to the root, once it is known.
+    fn strip<'a>(&self, path: &'a str) -> &'a str {
+        let Some(root) = self.root() else {
+            return path;
+        };
+        path.trim_start_matches("./")
+            .strip_prefix(root)
+            .and_then(|rest| rest.strip_prefix('/'))
+            .unwrap_or(path)
+    }
+}
+
+// The top-level directory `path` is in; a file at the top level has none.
+fn top_directory(path: &str, is_dir: bool) -> Option<&str> {
+    let path = path.trim_start_matches("./").trim_end_matches('/');
+    match path.split_once('/') {
+        Some((top, _)) => Some(top),
+        None if is_dir => Some(path),
+        None => None,
+    }
+    .filter(|top| !top.is_empty())
+}
+
+/// An entry read while the archive root was still a guess.
+struct PendingEntry {
+    archive_path: String,
+    size: u64,
+    content: Vec<u8>,
+}
+
+/// State carried across the entries of one archive.
+struct EntryProcessor {
+    filter: IntelligentFilter,
+    stats: FilterStats,
+    attributes: GitAttributes,
+    ignore_rules: IgnoreRules,
+    root: ArchiveRoot,
+    pending: Vec<PendingEntry>,
+    metrics: Vec<FileMetrics>,
+}
+
+impl EntryProcessor {
+    fn new(filter: &IntelligentFilter) -> Self {
+        Self::with_root(filter, ArchiveRoot::new())
+    }
+
+    fn with_root(filter: &IntelligentFilter, root: ArchiveRoot) -> Self {
+        Self {
+            filter: filter.clone(),
+            stats: FilterStats::for_filter(filter),
+            attributes: GitAttributes::new(),
+            ignore_rules: IgnoreRules::new(),
+            root,
+            pending: Vec::new(),
+            metrics: Vec::new(),
+        }
+    }
+
+    /// Records the path of every entry, directories included, for wrapper
+    /// directory detection. It must be called before the entry is processed.
+    fn observe(&mut self, file_path: &str, is_dir: bool) {
+        self.root.observe(file_path, is_dir);
+        if self.root.is_known() {
+            self.process_pending();
+        }
+    }
+
+    /// Whether the entry at `file_path` holds rules that apply to the entries
+    /// after it.
+    fn is_rules_file(&self, file_path: &str) -> bool {
+        file_path.rsplit('/').next() == Some(".gitattributes")
+            || self.filter.is_ignore_file(file_path)
+    }
+
+    /// Filters and counts one file. Every rule sees its path relative to the
+    /// archive root; the reported path keeps the root only when
+    /// [`IntelligentFilter::keep_archive_root`] is set.
+    ///
+    /// Until the root is known, entries are held back rather than filtered
+    /// on paths that may turn out to be wrong. Only as much of them is kept
+    /// as could be counted or sniffed.
+    fn process<R: Read>(&mut self, archive_path: &str, file_size: u64, reader: R) {
+        if !self.root.is_known() {
+            let limit = self.filter.max_file_size.max(SNIFF_LEN as u64);
+            let mut content = Vec::new();
+            if reader.take(limit).read_to_end(&mut content).is_ok() {
+                self.pending.push(PendingEntry {
+                    archive_path: archive_path.to_string(),
+                    size: file_size,
+                    content,
+                });
+            }
+            return;
+        }
+
+        let file_path = self.root.strip(archive_path).to_string();
+        if let Ok(metrics) = self.process_entry(&file_path, archive_path, file_size, reader) {
+            self.metrics.push(metrics);
+        }
+    }
+
+    fn process_entry<R: Read>(
+        &mut self,
+        file_path: &str,
+        archive_path: &str,
+        file_size: u64,
+        mut reader: R,
+    ) -> Result<FileMetrics> {
+        let reported_path = if self.filter.keep_archive_root {
+            archive_path
+        } else {
+            file_path
+        };
+        let mut content = Vec::new();
+
+        // `.gitattributes` and ignore files apply to the entries that follow
+        // them, even though the files themselves are usually filtered out as
+        // hidden.
+        let is_attributes_file = file_path.rsplit('/').next() == Some(".gitattributes");
+        let is_ignore_file = self.filter.is_ignore_file(file_path);
+        if (is_attributes_file || is_ignore_file) && reader.read_to_end(&mut content).is_ok() {
+            let rules = String::from_utf8_lossy(&content);
+            if is_attributes_file {
+                self.attributes.add_file(file_path, &rules);
+            }
+            if is_ignore_file {
+                self.ignore_rules.add_file(file_path, &rules);
+            }
+        }
+
+        // Entries outside the requested subpath aren't part of the project,
+        // so they don't show up in the filter stats either.
+        if !self.filter.is_within_subpath(file_path) {
+            return Err(AnalysisError::archive("Outside subpath"));
+        }
+
+        if file_size == 0 {
+            self.stats
+                .record_decision(reported_path, file_size, FilterDecision::Empty);
+            return Err(AnalysisError::archive("Empty file"));
+        }
+
+        let linguist = self.attributes.attributes_for(file_path);
+        let mut decision = self.filter.decide_with_ignore_rules(
+            file_path,
+            file_size,
+            &linguist,
+            &self.ignore_rules,
+        );
+
+        // Files whose extension doesn't give them away are caught by content,
+        // ahead of the same rules the filter checks binary extensions before.
+        // One byte past the sample tells a cut-off character from an invalid
+        // one.
+        if content.is_empty() {
+            let _ = (&mut reader)
+                .take(SNIFF_LEN as u64 + 1)
+                .read_to_end(&mut content);
+        }
+        if !matches!(decision, FilterDecision::LinguistAttribute(_)) && is_binary_content(&content)
+        {
+            decision = FilterDecision::Binary;
+        }
+        if decision == FilterDecision::Binary {
+            self.stats
+                .record_decision(reported_path, file_size, decision);
+            return Ok(FileMetrics::binary(reported_path, file_size));
+        }
+
+        // Generated files are read even when left out, so the summary can
+        // still split the lines into generated and handwritten ones.
+        let generated_by_name = matches!(decision, FilterDecision::Generated(_));
+        if (decision.is_included() || generated_by_name)
+            && reader.read_to_end(&mut content).is_err()
+        {
+            self.stats
+                .record_decision(reported_path, file_size, decision);
+            return Err(AnalysisError::archive("Failed to read file content"));
+        }
+
+        let (content, encoding) = decode_text(&content);
+
+        // Naming conventions were already checked by the filter; this also
+        // catches files whose header says they are generated.
+        let generated = decision
+            .is_included()
+            .then(|| generated_by(file_path, &content, &linguist))
+            .flatten();
+        if let Some(marker) = generated.filter(|_| !self.filter.count_generated) {
+            decision = FilterDecision::Generated(marker.to_string());
+        }
+
+        if matches!(decision, FilterDecision::Generated(_)) {
+            self.stats.generated_lines += content.lines().count();
+        }
+
+        let should_process = decision.is_included();
+        self.stats
+            .record_decision(reported_path, file_size, decision);
+
+        if !should_process {
+            return Err(AnalysisError::archive("File filtered out"));
+        }
+
+        let mut detection = LanguageRegistry::detect(file_path, content.as_bytes());
+        if let Some(language) = linguist
+            .language
+            .as_deref()
+            .and_then(LanguageRegistry::detect_by_name)
+        {
+            detection.language = Some(language);
+            detection.ambiguous_with.clear();
+        }
+
+        let language = detection
+            .language
+            .map(|l| l.name.clone())
+            .unwrap_or_else(|| "Text".to_string());
+
+        let metrics = analyze_file_content(reported_path, &content, &language, file_size)?;
+
+        Ok(metrics
+            .with_ambiguous_with(
+                detection
+                    .ambiguous_with
+                    .iter()
+                    .map(|l| l.name.clone())
+                    .collect(),
+            )
+            .with_encoding(encoding)
+            .with_generated(generated.is_some()))
+    }
+
+    fn process_pending(&mut self) {
+        for entry in std::mem::take(&mut self.pending) {
+            self.process(&entry.archive_path, entry.size, Cursor::new(entry.content));
+        }
+    }
+
+    fn finish(mut self) -> Result<ArchiveResults> {
+        self.root.settle();
+        self.process_pending();
+
+        let EntryProcessor {
+            filter,
+            stats,
+            root,
+            metrics,
+            ..
+        } = self;
+
+        if let Some(subpath) = &filter.subpath {
+            if stats.total_entries == 0 {
+                return Err(AnalysisError::archive(format!(
+                    "Path '{}' not found in archive",
+                    subpath
+                )));
+            }
+        }
+
+        Ok(ArchiveResults {
+            metrics,
+            stats,
+            root: root.root().map(str::to_string),
+        })
+    }
+}
+
+fn analyze_file_content(
+    file_path: &str,
+    content: &str,
+    language: &str,
+    file_size: u64,
+) -> Result<FileMetrics> {
+    let (counts, embedded) =
+        LineCounter::count_with_embedded(content, LanguageRegistry::get_language(language));
+
+    let metrics = FileMetrics::new(
+        file_path,
+        language.to_string(),
+        counts.total_lines,
+        counts.code_lines,
+        counts.comment_lines,
+        counts.blank_lines,
+    )?
+    .with_doc_comment_lines(counts.doc_comment_lines)
+    .with_embedded(embedded)
+    .with_size_bytes(file_size);
+
+    Ok(metrics)
+}
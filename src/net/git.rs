@@ -0,0 +1,860 @@
+//! Read-only access to a local git object database.
+//!
+//! Loose objects and packfiles, including offset and reference deltas, are
+//! decoded directly so a repository can be analyzed at any revision without a
+//! `git` binary or a checkout. Only SHA-1 repositories are supported.
+
+use crate::core::error::{AnalysisError, Result};
+use flate2::read::ZlibDecoder;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+const ID_LEN: usize = 20;
+const MAX_SYMREF_DEPTH: usize = 5;
+// Delta bases are cached while walking a tree, since neighbouring blobs tend
+// to share them; the cache is dropped once it grows past this many bytes.
+const BASE_CACHE_LIMIT: usize = 64 * 1024 * 1024;
+// Object sizes come from the repository and can't be trusted, so buffers
+// only reserve up to this many bytes ahead and grow as data arrives.
+const MAX_RESERVED_SIZE: u64 = 16 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ObjectId([u8; ID_LEN]);
+
+impl ObjectId {
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        if hex.len() != ID_LEN * 2 {
+            return None;
+        }
+
+        let mut bytes = [0u8; ID_LEN];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(hex.get(i * 2..i * 2 + 2)?, 16).ok()?;
+        }
+        Some(Self(bytes))
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        bytes.try_into().ok().map(Self)
+    }
+
+    pub fn to_hex(&self) -> String {
+        self.0.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+}
+
+impl fmt::Display for ObjectId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_hex())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectKind {
+    Commit,
+    Tree,
+    Blob,
+    Tag,
+}
+
+impl ObjectKind {
+    fn from_name(name: &[u8]) -> Option<Self> {
+        match name {
+            b"commit" => Some(ObjectKind::Commit),
+            b"tree" => Some(ObjectKind::Tree),
+            b"blob" => Some(ObjectKind::Blob),
+            b"tag" => Some(ObjectKind::Tag),
+            _ => None,
+        }
+    }
+
+    fn from_pack_type(kind: u8) -> Option<Self> {
+        match kind {
+            1 => Some(ObjectKind::Commit),
+            2 => Some(ObjectKind::Tree),
+            3 => Some(ObjectKind::Blob),
+            4 => Some(ObjectKind::Tag),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TreeEntry {
+    pub mode: u32,
+    pub name: String,
+    pub id: ObjectId,
+}
+
+impl TreeEntry {
+    pub fn is_tree(&self) -> bool {
+        self.mode == 0o040000
+    }
+
+    /// Regular files only; symbolic links and submodules carry no content of
+    /// their own.
+    pub fn is_file(&self) -> bool {
+        self.mode & 0o170000 == 0o100000
+    }
+}
+
+/// A `.git` directory or bare repository opened for reading.
+pub struct GitRepository {
+    git_dir: PathBuf,
+    common_dir: PathBuf,
+    object_dirs: Vec<PathBuf>,
+    packs: Vec<Pack>,
+    packed_refs: Vec<(String, ObjectId)>,
+    base_cache: HashMap<(usize, u64), (ObjectKind, Vec<u8>)>,
+    base_cache_size: usize,
+}
+
+impl GitRepository {
+    /// Opens the repository at `path`, which may be a bare repository, a
+    /// `.git` directory, or a working tree containing one.
+    pub fn open(path: &Path) -> Result<Self> {
+        let git_dir = find_git_dir(path)?;
+
+        // Linked worktrees keep their own HEAD but share everything else.
+        let common_dir = match fs::read_to_string(git_dir.join("commondir")) {
+            Ok(common) => git_dir.join(common.trim()),
+            Err(_) => git_dir.clone(),
+        };
+
+        if let Ok(config) = fs::read_to_string(common_dir.join("config")) {
+            let sha256 = config.lines().any(|line| {
+                let line = line.trim().to_ascii_lowercase().replace(' ', "");
+                line == "objectformat=sha256"
+            });
+            if sha256 {
+                return Err(AnalysisError::git("SHA-256 repositories are not supported"));
+            }
+        }
+
+        let mut object_dirs = vec![common_dir.join("objects")];
+        if let Ok(alternates) = fs::read_to_string(common_dir.join("objects/info/alternates")) {
+            for line in alternates.lines().map(str::trim) {
+                if !line.is_empty() && !line.starts_with('#') {
+                    object_dirs.push(common_dir.join("objects").join(line));
+                }
+            }
+        }
+
+        let mut packs = Vec::new();
+        for object_dir in &object_dirs {
+            let Ok(entries) = fs::read_dir(object_dir.join("pack")) else {
+                continue;
+            };
+            let mut index_paths: Vec<PathBuf> = entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.extension().is_some_and(|ext| ext == "idx"))
+                .collect();
+            index_paths.sort();
+
+            for index_path in index_paths {
+                packs.push(Pack::open(&index_path)?);
+            }
+        }
+
+        let packed_refs = fs::read_to_string(common_dir.join("packed-refs"))
+            .map(|content| parse_packed_refs(&content))
+            .unwrap_or_default();
+
+        Ok(Self {
+            git_dir,
+            common_dir,
+            object_dirs,
+            packs,
+            packed_refs,
+            base_cache: HashMap::new(),
+            base_cache_size: 0,
+        })
+    }
+
+    /// Resolves a revision to a commit. Accepts full and abbreviated object
+    /// names, `HEAD`, branch, tag and remote names, and `~<n>` and `^<n>`
+    /// parent suffixes. Annotated tags are peeled.
+    pub fn resolve(&mut self, rev: &str) -> Result<ObjectId> {
+        let split = rev.find(['~', '^']).unwrap_or(rev.len());
+        let (name, suffix) = rev.split_at(split);
+        let invalid = || AnalysisError::git(format!("Invalid revision '{}'", rev));
+
+        let mut steps = Vec::new();
+        let mut chars = suffix.chars().peekable();
+        while let Some(op) = chars.next() {
+            if !matches!(op, '~' | '^') {
+                return Err(invalid());
+            }
+            let mut digits = String::new();
+            while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+                digits.push(digit);
+            }
+            let count: usize = match digits.as_str() {
+                "" => 1,
+                n => n.parse().map_err(|_| invalid())?,
+            };
+            steps.push((op, count));
+        }
+
+        let id = self
+            .resolve_name(name)?
+            .ok_or_else(|| AnalysisError::git(format!("Unknown revision '{}'", rev)))?;
+        let mut commit = self.peel_to_commit(id)?;
+
+        for (op, count) in steps {
+            commit = match op {
+                '~' => (0..count).try_fold(commit, |commit, _| self.parent(commit, 1, rev))?,
+                _ if count == 0 => commit,
+                _ => self.parent(commit, count, rev)?,
+            };
+        }
+
+        Ok(commit)
+    }
+
+    /// The root tree of a commit.
+    pub fn commit_tree(&mut self, commit: ObjectId) -> Result<ObjectId> {
+        let data = self.read_kind(commit, ObjectKind::Commit)?;
+        let tree = header_ids(&data, b"tree ").next();
+        tree.ok_or_else(|| AnalysisError::git(format!("Commit {} has no tree", commit)))
+    }
+
+    pub fn read_tree(&mut self, tree: ObjectId) -> Result<Vec<TreeEntry>> {
+        let data = self.read_kind(tree, ObjectKind::Tree)?;
+        let corrupt = || AnalysisError::git(format!("Corrupt tree {}", tree));
+
+        let mut entries = Vec::new();
+        let mut rest = data.as_slice();
+        while !rest.is_empty() {
+            let space = rest.iter().position(|&b| b == b' ').ok_or_else(corrupt)?;
+            let nul = rest.iter().position(|&b| b == 0).ok_or_else(corrupt)?;
+            if nul < space || rest.len() < nul + 1 + ID_LEN {
+                return Err(corrupt());
+            }
+
+            let mode = std::str::from_utf8(&rest[..space])
+                .ok()
+                .and_then(|mode| u32::from_str_radix(mode, 8).ok())
+                .ok_or_else(corrupt)?;
+            let name = String::from_utf8_lossy(&rest[space + 1..nul]).to_string();
+            let id = ObjectId::from_bytes(&rest[nul + 1..nul + 1 + ID_LEN]).ok_or_else(corrupt)?;
+
+            entries.push(TreeEntry { mode, name, id });
+            rest = &rest[nul + 1 + ID_LEN..];
+        }
+
+        Ok(entries)
+    }
+
+    pub fn read_blob(&mut self, blob: ObjectId) -> Result<Vec<u8>> {
+        self.read_kind(blob, ObjectKind::Blob)
+    }
+
+    /// The size of an object's content, read from its header without
+    /// inflating or resolving the whole object where possible.
+    pub fn object_size(&mut self, id: ObjectId) -> Result<u64> {
+        if let Some(path) = self.loose_path(id) {
+            let file = File::open(&path)
+                .map_err(|e| AnalysisError::file_read(path.to_string_lossy(), e))?;
+            let mut header = Vec::new();
+            ZlibDecoder::new(file)
+                .take(32)
+                .read_to_end(&mut header)
+                .map_err(|e| AnalysisError::git(format!("Corrupt object {}: {}", id, e)))?;
+            let (_, size, _) = parse_loose_header(&header)
+                .ok_or_else(|| AnalysisError::git(format!("Corrupt object {}", id)))?;
+            return Ok(size);
+        }
+
+        let (pack, offset) = self
+            .find_packed(id)
+            .ok_or_else(|| AnalysisError::git(format!("Object {} not found", id)))?;
+        self.packs[pack].entry_size(offset)
+    }
+
+    pub fn read_object(&mut self, id: ObjectId) -> Result<(ObjectKind, Vec<u8>)> {
+        if let Some(path) = self.loose_path(id) {
+            let file = File::open(&path)
+                .map_err(|e| AnalysisError::file_read(path.to_string_lossy(), e))?;
+            let mut data = Vec::new();
+            ZlibDecoder::new(file)
+                .read_to_end(&mut data)
+                .map_err(|e| AnalysisError::git(format!("Corrupt object {}: {}", id, e)))?;
+
+            let (kind, size, header_len) = parse_loose_header(&data)
+                .ok_or_else(|| AnalysisError::git(format!("Corrupt object {}", id)))?;
+            data.drain(..header_len);
+            if data.len() as u64 != size {
+                return Err(AnalysisError::git(format!("Truncated object {}", id)));
+            }
+            return Ok((kind, data));
+        }
+
+        match self.find_packed(id) {
+            Some((pack, offset)) => self.read_packed(pack, offset),
+            None => Err(AnalysisError::git(format!("Object {} not found", id))),
+        }
+    }
+
+    fn read_kind(&mut self, id: ObjectId, expected: ObjectKind) -> Result<Vec<u8>> {
+        match self.read_object(id)? {
+            (kind, data) if kind == expected => Ok(data),
+            (kind, _) => Err(AnalysisError::git(format!(
+                "Object {} is a {:?}, expected a {:?}",
+                id, kind, expected
+            ))),
+        }
+    }
+
+    fn read_packed(&mut self, pack: usize, offset: u64) -> Result<(ObjectKind, Vec<u8>)> {
+        if let Some((kind, data)) = self.base_cache.get(&(pack, offset)) {
+            return Ok((*kind, data.clone()));
+        }
+
+        let (kind, data) = match self.packs[pack].read_entry(offset)? {
+            PackEntry::Object(kind, data) => (kind, data),
+            PackEntry::OffsetDelta(base_offset, delta) => {
+                let (kind, base) = self.read_packed(pack, base_offset)?;
+                self.cache_base(pack, base_offset, kind, &base);
+                (kind, apply_delta(&base, &delta)?)
+            }
+            PackEntry::RefDelta(base_id, delta) => {
+                let (kind, base) = self.read_object(base_id)?;
+                (kind, apply_delta(&base, &delta)?)
+            }
+        };
+
+        Ok((kind, data))
+    }
+
+    fn cache_base(&mut self, pack: usize, offset: u64, kind: ObjectKind, data: &[u8]) {
+        if self.base_cache_size + data.len() > BASE_CACHE_LIMIT {
+            self.base_cache.clear();
+            self.base_cache_size = 0;
+        }
+        if self
+            .base_cache
+            .insert((pack, offset), (kind, data.to_vec()))
+            .is_none()
+        {
+            self.base_cache_size += data.len();
+        }
+    }
+
+    fn loose_path(&self, id: ObjectId) -> Option<PathBuf> {
+        let hex = id.to_hex();
+        self.object_dirs
+            .iter()
+            .map(|dir| dir.join(&hex[..2]).join(&hex[2..]))
+            .find(|path| path.is_file())
+    }
+
+    fn find_packed(&self, id: ObjectId) -> Option<(usize, u64)> {
+        self.packs
+            .iter()
+            .enumerate()
+            .find_map(|(index, pack)| pack.index.find(&id).map(|offset| (index, offset)))
+    }
+
+    fn resolve_name(&mut self, name: &str) -> Result<Option<ObjectId>> {
+        if let Some(id) = ObjectId::from_hex(name) {
+            return Ok(Some(id));
+        }
+
+        // The same lookup order as `git rev-parse`.
+        let candidates = [
+            name.to_string(),
+            format!("refs/{}", name),
+            format!("refs/tags/{}", name),
+            format!("refs/heads/{}", name),
+            format!("refs/remotes/{}", name),
+            format!("refs/remotes/{}/HEAD", name),
+        ];
+        for candidate in &candidates {
+            if let Some(id) = self.read_ref(candidate, 0)? {
+                return Ok(Some(id));
+            }
+        }
+
+        if name.len() >= 4 && name.chars().all(|c| c.is_ascii_hexdigit()) {
+            return self.find_abbreviated(&name.to_ascii_lowercase());
+        }
+
+        Ok(None)
+    }
+
+    fn read_ref(&self, name: &str, depth: usize) -> Result<Option<ObjectId>> {
+        if depth > MAX_SYMREF_DEPTH || name.split('/').any(|part| part == "..") {
+            return Ok(None);
+        }
+
+        for dir in [&self.git_dir, &self.common_dir] {
+            let Ok(content) = fs::read_to_string(dir.join(name)) else {
+                continue;
+            };
+            let content = content.trim();
+            if let Some(target) = content.strip_prefix("ref:") {
+                return self.read_ref(target.trim(), depth + 1);
+            }
+            if let Some(id) = ObjectId::from_hex(content) {
+                return Ok(Some(id));
+            }
+        }
+
+        Ok(self
+            .packed_refs
+            .iter()
+            .find(|(packed, _)| packed == name)
+            .map(|(_, id)| *id))
+    }
+
+    fn find_abbreviated(&self, prefix: &str) -> Result<Option<ObjectId>> {
+        let mut matches: Vec<ObjectId> = Vec::new();
+
+        for dir in &self.object_dirs {
+            let Ok(entries) = fs::read_dir(dir.join(&prefix[..2])) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let hex = format!("{}{}", &prefix[..2], entry.file_name().to_string_lossy());
+                if hex.starts_with(prefix) {
+                    matches.extend(ObjectId::from_hex(&hex));
+                }
+            }
+        }
+        for pack in &self.packs {
+            matches.extend(pack.index.find_prefix(prefix));
+        }
+
+        matches.sort();
+        matches.dedup();
+        match matches.as_slice() {
+            [] => Ok(None),
+            [id] => Ok(Some(*id)),
+            _ => Err(AnalysisError::git(format!(
+                "Short object name '{}' is ambiguous",
+                prefix
+            ))),
+        }
+    }
+
+    fn peel_to_commit(&mut self, mut id: ObjectId) -> Result<ObjectId> {
+        loop {
+            let (kind, data) = self.read_object(id)?;
+            match kind {
+                ObjectKind::Commit => return Ok(id),
+                ObjectKind::Tag => {
+                    id = header_ids(&data, b"object ")
+                        .next()
+                        .ok_or_else(|| AnalysisError::git(format!("Tag {} has no target", id)))?;
+                }
+                _ => {
+                    return Err(AnalysisError::git(format!(
+                        "Object {} is a {:?}, not a commit",
+                        id, kind
+                    )))
+                }
+            }
+        }
+    }
+
+    fn parent(&mut self, commit: ObjectId, number: usize, rev: &str) -> Result<ObjectId> {
+        let data = self.read_kind(commit, ObjectKind::Commit)?;
+        let parent = header_ids(&data, b"parent ").nth(number - 1);
+        parent.ok_or_else(|| AnalysisError::git(format!("Revision '{}' does not exist", rev)))
+    }
+}
+
+fn find_git_dir(path: &Path) -> Result<PathBuf> {
+    let dot_git = path.join(".git");
+
+    // Submodules and linked worktrees have a `.git` file pointing elsewhere.
+    if dot_git.is_file() {
+        let content = fs::read_to_string(&dot_git)
+            .map_err(|e| AnalysisError::file_read(dot_git.to_string_lossy(), e))?;
+        if let Some(target) = content.trim().strip_prefix("gitdir:") {
+            return Ok(path.join(target.trim()));
+        }
+    }
+    if dot_git.join("objects").is_dir() {
+        return Ok(dot_git);
+    }
+    if path.join("objects").is_dir() && path.join("HEAD").is_file() {
+        return Ok(path.to_path_buf());
+    }
+
+    Err(AnalysisError::git(format!(
+        "Not a git repository: {}",
+        path.display()
+    )))
+}
+
+fn parse_packed_refs(content: &str) -> Vec<(String, ObjectId)> {
+    content
+        .lines()
+        .filter(|line| !line.starts_with('#') && !line.starts_with('^'))
+        .filter_map(|line| {
+            let (hex, name) = line.split_once(' ')?;
+            Some((name.trim().to_string(), ObjectId::from_hex(hex)?))
+        })
+        .collect()
+}
+
+fn parse_loose_header(data: &[u8]) -> Option<(ObjectKind, u64, usize)> {
+    let nul = data.iter().position(|&b| b == 0)?;
+    let header = &data[..nul];
+    let space = header.iter().position(|&b| b == b' ')?;
+    let kind = ObjectKind::from_name(&header[..space])?;
+    let size = std::str::from_utf8(&header[space + 1..])
+        .ok()?
+        .parse()
+        .ok()?;
+    Some((kind, size, nul + 1))
+}
+
+/// Object ids named by `key` lines in a commit or tag header.
+fn header_ids<'a>(data: &'a [u8], key: &'a [u8]) -> impl Iterator<Item = ObjectId> + 'a {
+    data.split(|&b| b == b'\n')
+        .take_while(|line| !line.is_empty())
+        .filter_map(move |line| line.strip_prefix(key))
+        .filter_map(|hex| ObjectId::from_hex(std::str::from_utf8(hex).ok()?))
+}
+
+/// Rebuilds an object from its delta base and a git delta: the base and
+/// result sizes followed by copy and insert instructions.
+fn apply_delta(base: &[u8], delta: &[u8]) -> Result<Vec<u8>> {
+    let corrupt = || AnalysisError::git("Corrupt delta");
+    let mut pos = 0;
+
+    let base_size = read_size(delta, &mut pos).ok_or_else(corrupt)?;
+    let result_size = read_size(delta, &mut pos).ok_or_else(corrupt)?;
+    if base_size != base.len() as u64 {
+        return Err(corrupt());
+    }
+
+    let mut result = Vec::with_capacity(result_size.min(MAX_RESERVED_SIZE) as usize);
+    while pos < delta.len() {
+        let op = delta[pos];
+        pos += 1;
+
+        if op & 0x80 != 0 {
+            let mut offset = 0usize;
+            let mut size = 0usize;
+            for i in 0..4 {
+                if op & (1 << i) != 0 {
+                    offset |= (*delta.get(pos).ok_or_else(corrupt)? as usize) << (i * 8);
+                    pos += 1;
+                }
+            }
+            for i in 0..3 {
+                if op & (0x10 << i) != 0 {
+                    size |= (*delta.get(pos).ok_or_else(corrupt)? as usize) << (i * 8);
+                    pos += 1;
+                }
+            }
+            if size == 0 {
+                size = 0x10000;
+            }
+            let copy = base.get(offset..offset + size).ok_or_else(corrupt)?;
+            result.extend_from_slice(copy);
+        } else if op != 0 {
+            let insert = delta.get(pos..pos + op as usize).ok_or_else(corrupt)?;
+            result.extend_from_slice(insert);
+            pos += op as usize;
+        } else {
+            return Err(corrupt());
+        }
+    }
+
+    if result.len() as u64 != result_size {
+        return Err(corrupt());
+    }
+    Ok(result)
+}
+
+// Little-endian base-128 size used in delta headers.
+fn read_size(data: &[u8], pos: &mut usize) -> Option<u64> {
+    let mut size = 0u64;
+    let mut shift = 0;
+    loop {
+        let byte = *data.get(*pos)?;
+        *pos += 1;
+        size |= ((byte & 0x7f) as u64) << shift;
+        shift += 7;
+        if byte & 0x80 == 0 || shift > 63 {
+            return Some(size);
+        }
+    }
+}
+
+enum PackEntry {
+    Object(ObjectKind, Vec<u8>),
+    OffsetDelta(u64, Vec<u8>),
+    RefDelta(ObjectId, Vec<u8>),
+}
+
+struct Pack {
+    path: PathBuf,
+    file: File,
+    index: PackIndex,
+}
+
+impl Pack {
+    fn open(index_path: &Path) -> Result<Self> {
+        let path = index_path.with_extension("pack");
+        let file =
+            File::open(&path).map_err(|e| AnalysisError::file_read(path.to_string_lossy(), e))?;
+        let index = PackIndex::open(index_path)?;
+        Ok(Self { path, file, index })
+    }
+
+    fn reader_at(&self, offset: u64) -> Result<BufReader<&File>> {
+        let mut reader = BufReader::new(&self.file);
+        reader
+            .seek(SeekFrom::Start(offset))
+            .map_err(|e| AnalysisError::file_read(self.path.to_string_lossy(), e))?;
+        Ok(reader)
+    }
+
+    fn read_entry(&self, offset: u64) -> Result<PackEntry> {
+        let mut reader = self.reader_at(offset)?;
+        let (kind, size) = self.read_entry_header(&mut reader, offset)?;
+
+        match kind {
+            6 => {
+                let distance = self.read_offset(&mut reader)?;
+                let base_offset = offset
+                    .checked_sub(distance)
+                    .ok_or_else(|| self.corrupt(offset))?;
+                Ok(PackEntry::OffsetDelta(
+                    base_offset,
+                    self.inflate(reader, size, offset)?,
+                ))
+            }
+            7 => {
+                let mut id = [0u8; ID_LEN];
+                reader
+                    .read_exact(&mut id)
+                    .map_err(|_| self.corrupt(offset))?;
+                Ok(PackEntry::RefDelta(
+                    ObjectId(id),
+                    self.inflate(reader, size, offset)?,
+                ))
+            }
+            kind => {
+                let kind = ObjectKind::from_pack_type(kind).ok_or_else(|| self.corrupt(offset))?;
+                Ok(PackEntry::Object(kind, self.inflate(reader, size, offset)?))
+            }
+        }
+    }
+
+    /// The inflated size of an entry; for deltas, the size of the object the
+    /// delta produces.
+    fn entry_size(&self, offset: u64) -> Result<u64> {
+        let mut reader = self.reader_at(offset)?;
+        let (kind, size) = self.read_entry_header(&mut reader, offset)?;
+
+        match kind {
+            6 | 7 => {
+                if kind == 6 {
+                    self.read_offset(&mut reader)?;
+                } else {
+                    reader
+                        .read_exact(&mut [0u8; ID_LEN])
+                        .map_err(|_| self.corrupt(offset))?;
+                }
+
+                let mut header = Vec::new();
+                ZlibDecoder::new(reader)
+                    .take(20)
+                    .read_to_end(&mut header)
+                    .map_err(|_| self.corrupt(offset))?;
+                let mut pos = 0;
+                read_size(&header, &mut pos).ok_or_else(|| self.corrupt(offset))?;
+                read_size(&header, &mut pos).ok_or_else(|| self.corrupt(offset))
+            }
+            _ => Ok(size),
+        }
+    }
+
+    fn read_entry_header(&self, reader: &mut impl Read, offset: u64) -> Result<(u8, u64)> {
+        let mut byte = self.read_byte(reader, offset)?;
+        let kind = (byte >> 4) & 0x07;
+        let mut size = (byte & 0x0f) as u64;
+        let mut shift = 4;
+        while byte & 0x80 != 0 {
+            byte = self.read_byte(reader, offset)?;
+            size |= ((byte & 0x7f) as u64) << shift;
+            shift += 7;
+        }
+        Ok((kind, size))
+    }
+
+    // The distance back to an offset delta's base, in git's big-endian
+    // variant of base-128 where each continuation adds one.
+    fn read_offset(&self, reader: &mut impl Read) -> Result<u64> {
+        let mut byte = self.read_byte(reader, 0)?;
+        let mut distance = (byte & 0x7f) as u64;
+        while byte & 0x80 != 0 {
+            byte = self.read_byte(reader, 0)?;
+            distance = ((distance + 1) << 7) | (byte & 0x7f) as u64;
+        }
+        Ok(distance)
+    }
+
+    fn read_byte(&self, reader: &mut impl Read, offset: u64) -> Result<u8> {
+        let mut byte = [0u8; 1];
+        reader
+            .read_exact(&mut byte)
+            .map_err(|_| self.corrupt(offset))?;
+        Ok(byte[0])
+    }
+
+    fn inflate(&self, reader: impl Read, size: u64, offset: u64) -> Result<Vec<u8>> {
+        let mut data = Vec::with_capacity(size.min(MAX_RESERVED_SIZE) as usize);
+        ZlibDecoder::new(reader)
+            .take(size)
+            .read_to_end(&mut data)
+            .map_err(|_| self.corrupt(offset))?;
+        if data.len() as u64 != size {
+            return Err(self.corrupt(offset));
+        }
+        Ok(data)
+    }
+
+    fn corrupt(&self, offset: u64) -> AnalysisError {
+        AnalysisError::git(format!(
+            "Corrupt pack entry at offset {} in {}",
+            offset,
+            self.path.display()
+        ))
+    }
+}
+
+/// A pack `.idx` file, kept in its on-disk layout and searched in place.
+struct PackIndex {
+    data: Vec<u8>,
+    version: u32,
+    count: usize,
+}
+
+impl PackIndex {
+    const V2_MAGIC: &'static [u8] = b"\xfftOc";
+    const FANOUT_LEN: usize = 256 * 4;
+
+    fn open(path: &Path) -> Result<Self> {
+        let data =
+            fs::read(path).map_err(|e| AnalysisError::file_read(path.to_string_lossy(), e))?;
+        let corrupt = || AnalysisError::git(format!("Corrupt pack index {}", path.display()));
+
+        let version = if data.starts_with(Self::V2_MAGIC) {
+            read_u32(&data, 4).ok_or_else(corrupt)?
+        } else {
+            1
+        };
+        if version != 1 && version != 2 {
+            return Err(AnalysisError::git(format!(
+                "Unsupported pack index version {} in {}",
+                version,
+                path.display()
+            )));
+        }
+
+        let mut index = Self {
+            data,
+            version,
+            count: 0,
+        };
+        index.count = index.fanout(255).ok_or_else(corrupt)? as usize;
+
+        let entries_len = match version {
+            1 => index.count * (4 + ID_LEN),
+            _ => index.count * (ID_LEN + 4 + 4),
+        };
+        if index.data.len() < index.entries_start() + entries_len {
+            return Err(corrupt());
+        }
+
+        Ok(index)
+    }
+
+    fn entries_start(&self) -> usize {
+        match self.version {
+            1 => Self::FANOUT_LEN,
+            _ => 8 + Self::FANOUT_LEN,
+        }
+    }
+
+    fn fanout(&self, byte: u8) -> Option<u32> {
+        let start = if self.version == 1 { 0 } else { 8 };
+        read_u32(&self.data, start + byte as usize * 4)
+    }
+
+    fn id_at(&self, i: usize) -> &[u8] {
+        let start = match self.version {
+            1 => self.entries_start() + i * (4 + ID_LEN) + 4,
+            _ => self.entries_start() + i * ID_LEN,
+        };
+        &self.data[start..start + ID_LEN]
+    }
+
+    fn offset_at(&self, i: usize) -> Option<u64> {
+        if self.version == 1 {
+            return read_u32(&self.data, self.entries_start() + i * (4 + ID_LEN)).map(u64::from);
+        }
+
+        let offsets = self.entries_start() + self.count * (ID_LEN + 4);
+        let offset = read_u32(&self.data, offsets + i * 4)?;
+        if offset & 0x8000_0000 == 0 {
+            return Some(offset as u64);
+        }
+
+        // Offsets past 2 GiB live in a separate table of 64-bit values.
+        let large = offsets + self.count * 4 + (offset & 0x7fff_ffff) as usize * 8;
+        let bytes = self.data.get(large..large + 8)?;
+        Some(u64::from_be_bytes(bytes.try_into().ok()?))
+    }
+
+    // The range of entries whose ids start with `byte`.
+    fn bucket(&self, byte: u8) -> (usize, usize) {
+        let end = self.fanout(byte).unwrap_or(0) as usize;
+        let start = match byte {
+            0 => 0,
+            _ => self.fanout(byte - 1).unwrap_or(0) as usize,
+        };
+        (start, end.max(start))
+    }
+
+    fn find(&self, id: &ObjectId) -> Option<u64> {
+        let (mut low, mut high) = self.bucket(id.0[0]);
+        while low < high {
+            let mid = (low + high) / 2;
+            match self.id_at(mid).cmp(&id.0[..]) {
+                std::cmp::Ordering::Less => low = mid + 1,
+                std::cmp::Ordering::Greater => high = mid,
+                std::cmp::Ordering::Equal => return self.offset_at(mid),
+            }
+        }
+        None
+    }
+
+    fn find_prefix(&self, prefix: &str) -> Vec<ObjectId> {
+        let Ok(first) = u8::from_str_radix(&prefix[..2], 16) else {
+            return Vec::new();
+        };
+        let (start, end) = self.bucket(first);
+
+        (start..end)
+            .filter_map(|i| ObjectId::from_bytes(self.id_at(i)))
+            .filter(|id| id.to_hex().starts_with(prefix))
+            .collect()
+    }
+}
+
+fn read_u32(data: &[u8], at: usize) -> Option<u32> {
+    let bytes = data.get(at..at + 4)?;
+    Some(u32::from_be_bytes(bytes.try_into().ok()?))
+}
@@ -1,15 +1,20 @@
+pub mod extract;
+pub mod git;
 pub mod providers;
 pub mod stream;
 pub mod traits;
 
 use crate::core::{
-    analysis::ProjectAnalysis,
+    analysis::{ProjectAnalysis, DEFAULT_DIRECTORY_DEPTH},
     error::{AnalysisError, Result},
     filter::IntelligentFilter,
 };
+use extract::ARCHIVE_SUFFIXES;
 use providers::*;
 use reqwest::Client;
 use std::collections::HashMap;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use traits::{GitProvider, NoOpProgressHook};
 
@@ -42,6 +47,7 @@ pub struct RemoteAnalyzer {
     filter: IntelligentFilter,
     progress_hook: Arc<dyn ProgressHook>,
     provider_configs: HashMap<String, ProviderConfig>,
+    directory_depth: usize,
 }
 
 impl RemoteAnalyzer {
@@ -53,6 +59,7 @@ impl RemoteAnalyzer {
             filter: IntelligentFilter::default(),
             progress_hook: Arc::new(NoOpProgressHook),
             provider_configs: HashMap::new(),
+            directory_depth: DEFAULT_DIRECTORY_DEPTH,
         };
 
         analyzer.register_default_providers();
@@ -208,6 +215,14 @@ impl RemoteAnalyzer {
         }
     }
 
+    /// Set how many directory levels the directory tree rolls up
+    ///
+    /// # Arguments
+    /// * `depth` - Number of levels below the project root (0 disables it)
+    pub fn set_directory_depth(&mut self, depth: usize) {
+        self.directory_depth = depth;
+    }
+
     // Legacy methods for backward compatibility
 
     /// Set timeout for all providers (legacy method)
@@ -241,7 +256,7 @@ impl RemoteAnalyzer {
         let config = self
             .provider_configs
             .entry(provider_name.to_string())
-            .or_insert_with(ProviderConfig::default);
+            .or_default();
 
         config.credentials.extend(credentials);
         self.apply_config_to_providers();
@@ -266,24 +281,43 @@ impl RemoteAnalyzer {
     ///     // Shorthand notation
     ///     let analysis = analyzer.analyze_url("user/repo@main").await?;
     ///
+    ///     // A single directory of a monorepo
+    ///     let analysis = analyzer.analyze_url("org/mono@main:services/billing").await?;
+    ///
     ///     // Direct archive
     ///     let analysis = analyzer.analyze_url("https://example.com/project.tar.gz").await?;
+    ///
+    ///     // Local directory or archive
+    ///     let analysis = analyzer.analyze_url("./checkout").await?;
+    ///     let analysis = analyzer.analyze_url("file:///tmp/project.tar.gz").await?;
     ///     
     ///     Ok(())
     /// }
     /// ```
     pub async fn analyze_url(&self, url: &str) -> Result<ProjectAnalysis> {
-        let expanded_url = self.expand_url(url.trim());
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(path) = local_path(url.trim()) {
+            return self.analyze_path(path).await;
+        }
+
+        let (url, subpath) = split_subpath(url.trim());
+        let expanded_url = self.expand_url(url);
 
         // Try direct archive first for better performance
-        if expanded_url.ends_with(".tar.gz") || expanded_url.ends_with(".tgz") {
+        if ARCHIVE_SUFFIXES
+            .iter()
+            .any(|suffix| expanded_url.ends_with(suffix))
+        {
             return self.analyze_direct_tarball(&expanded_url).await;
         }
 
         // Try each provider
         for provider in &self.providers {
             if provider.can_handle(&expanded_url) {
-                if let Some(parsed) = provider.parse_url(&expanded_url) {
+                if let Some(mut parsed) = provider.parse_url(&expanded_url) {
+                    if let Some(subpath) = subpath {
+                        parsed = parsed.with_subpath(subpath.to_string());
+                    }
                     return self.analyze_with_provider(provider.as_ref(), &parsed).await;
                 }
             }
@@ -295,6 +329,127 @@ impl RemoteAnalyzer {
         )))
     }
 
+    /// Analyze a local directory or archive file without touching the network
+    ///
+    /// # Arguments
+    /// * `path` - Directory to walk, or archive file in any supported format
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// use bytes_radar::net::RemoteAnalyzer;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let analyzer = RemoteAnalyzer::new();
+    ///     let analysis = analyzer.analyze_path(".").await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn analyze_path(&self, path: impl AsRef<Path>) -> Result<ProjectAnalysis> {
+        let path = path.as_ref().to_path_buf();
+        if !path.exists() {
+            return Err(AnalysisError::file_read(
+                path.to_string_lossy(),
+                std::io::Error::from(std::io::ErrorKind::NotFound),
+            ));
+        }
+
+        let project_name = local_project_name(&path);
+        let mut project_analysis =
+            ProjectAnalysis::new(project_name).with_directory_depth(self.directory_depth);
+
+        self.progress_hook.on_processing_start("Processing...");
+
+        let filter = self.filter.clone();
+        tokio::task::spawn_blocking(move || {
+            stream::process_local_path(&path, &mut project_analysis, &filter)
+                .map(|_| project_analysis)
+        })
+        .await
+        .map_err(|e| AnalysisError::archive(format!("Task join error: {}", e)))?
+    }
+
+    /// Analyze a local git repository at a revision, reading objects straight
+    /// from its object database without a checkout or a `git` binary
+    ///
+    /// # Arguments
+    /// * `git_dir` - Bare repository, `.git` directory or working tree
+    /// * `rev` - Commit, branch, tag or `HEAD`, optionally with `~<n>`/`^<n>`
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// use bytes_radar::net::RemoteAnalyzer;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let analyzer = RemoteAnalyzer::new();
+    ///     let analysis = analyzer.analyze_git("./mirror.git", "v2.3.0").await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn analyze_git(
+        &self,
+        git_dir: impl AsRef<Path>,
+        rev: &str,
+    ) -> Result<ProjectAnalysis> {
+        let git_dir = git_dir.as_ref().to_path_buf();
+        let project_name = format!("{}@{}", git_project_name(&git_dir), rev);
+        let mut project_analysis =
+            ProjectAnalysis::new(project_name).with_directory_depth(self.directory_depth);
+
+        self.progress_hook.on_processing_start("Processing...");
+
+        let filter = self.filter.clone();
+        let rev = rev.to_string();
+        tokio::task::spawn_blocking(move || {
+            stream::process_git_revision(&git_dir, &rev, &mut project_analysis, &filter)
+                .map(|_| project_analysis)
+        })
+        .await
+        .map_err(|e| AnalysisError::archive(format!("Task join error: {}", e)))?
+    }
+
+    /// Analyze an archive read from any source, such as standard input
+    ///
+    /// The format is detected from the first bytes, as for downloads.
+    ///
+    /// # Arguments
+    /// * `reader` - Archive bytes in any supported format
+    /// * `project_name` - Name to report for the project
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// use bytes_radar::net::RemoteAnalyzer;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let analyzer = RemoteAnalyzer::new();
+    ///     let analysis = analyzer.analyze_reader(std::io::stdin(), "stdin").await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn analyze_reader<R: std::io::Read + Send + 'static>(
+        &self,
+        reader: R,
+        project_name: &str,
+    ) -> Result<ProjectAnalysis> {
+        let mut project_analysis =
+            ProjectAnalysis::new(project_name).with_directory_depth(self.directory_depth);
+
+        self.progress_hook.on_processing_start("Processing...");
+
+        let filter = self.filter.clone();
+        tokio::task::spawn_blocking(move || {
+            stream::process_archive_reader(reader, &mut project_analysis, &filter)
+                .map(|_| project_analysis)
+        })
+        .await
+        .map_err(|e| AnalysisError::archive(format!("Task join error: {}", e)))?
+    }
+
     /// Analyze using a specific provider
     async fn analyze_with_provider(
         &self,
@@ -327,10 +482,15 @@ impl RemoteAnalyzer {
             }
         }
 
+        let filter = IntelligentFilter {
+            subpath: parsed.subpath.clone(),
+            ..self.filter.clone()
+        };
+
         let mut failed_reasons: Vec<String> = Vec::new();
         for download_url in download_urls {
             match self
-                .analyze_direct_tarball_with_name(&download_url, &parsed.project_name)
+                .analyze_direct_tarball_with_name(&download_url, &parsed.project_name, &filter)
                 .await
             {
                 Ok(analysis) => return Ok(analysis),
@@ -399,17 +559,19 @@ impl RemoteAnalyzer {
     /// Analyze a direct archive URL
     async fn analyze_direct_tarball(&self, url: &str) -> Result<ProjectAnalysis> {
         let project_name = self.extract_project_name_from_url(url);
-        self.analyze_direct_tarball_with_name(url, &project_name)
+        self.analyze_direct_tarball_with_name(url, &project_name, &self.filter)
             .await
     }
 
-    /// Analyze a direct archive URL with custom project name
+    /// Analyze a direct archive URL with custom project name and filter
     async fn analyze_direct_tarball_with_name(
         &self,
         url: &str,
         project_name: &str,
+        filter: &IntelligentFilter,
     ) -> Result<ProjectAnalysis> {
-        let mut project_analysis = ProjectAnalysis::new(project_name);
+        let mut project_analysis =
+            ProjectAnalysis::new(project_name).with_directory_depth(self.directory_depth);
 
         let client = self.build_global_client()?;
 
@@ -450,7 +612,7 @@ impl RemoteAnalyzer {
             stream::process_tarball_stream(
                 stream_reader,
                 &mut project_analysis,
-                &self.filter,
+                filter,
                 self.progress_hook.as_ref(),
             )
             .await?;
@@ -472,7 +634,7 @@ impl RemoteAnalyzer {
             stream::process_tarball(
                 bytes,
                 &mut project_analysis,
-                &self.filter,
+                filter,
                 self.progress_hook.as_ref(),
             )
             .await?;
@@ -530,11 +692,10 @@ impl RemoteAnalyzer {
         let url_path = url.trim_end_matches('/');
 
         if let Some(filename) = url_path.split('/').next_back() {
-            if filename.ends_with(".tar.gz") {
-                return filename.trim_end_matches(".tar.gz").to_string();
-            }
-            if filename.ends_with(".tgz") {
-                return filename.trim_end_matches(".tgz").to_string();
+            for suffix in ARCHIVE_SUFFIXES {
+                if let Some(name) = filename.strip_suffix(suffix) {
+                    return name.to_string();
+                }
             }
             return filename.to_string();
         }
@@ -543,6 +704,66 @@ impl RemoteAnalyzer {
     }
 }
 
+/// Recognises local sources: `file://` URLs, and anything else that names an
+/// existing file or directory.
+#[cfg(not(target_arch = "wasm32"))]
+fn local_path(url: &str) -> Option<PathBuf> {
+    if let Some(path) = url.strip_prefix("file://") {
+        return Some(PathBuf::from(path));
+    }
+
+    let path = Path::new(url);
+    path.exists().then(|| path.to_path_buf())
+}
+
+/// Names a local project after its directory, or its archive file without
+/// the archive suffix.
+#[cfg(not(target_arch = "wasm32"))]
+fn local_project_name(path: &Path) -> String {
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let Some(file_name) = path.file_name() else {
+        return "local-project".to_string();
+    };
+
+    let file_name = file_name.to_string_lossy();
+    ARCHIVE_SUFFIXES
+        .iter()
+        .find_map(|suffix| file_name.strip_suffix(suffix))
+        .unwrap_or(&file_name)
+        .to_string()
+}
+
+/// Names a repository after its directory, dropping a `.git` suffix, or
+/// after the working tree when given its `.git` directory.
+#[cfg(not(target_arch = "wasm32"))]
+fn git_project_name(git_dir: &Path) -> String {
+    let path = git_dir
+        .canonicalize()
+        .unwrap_or_else(|_| git_dir.to_path_buf());
+    let path = match path.file_name() {
+        Some(name) if name == ".git" => path.parent().unwrap_or(&path),
+        _ => &path,
+    };
+
+    path.file_name()
+        .map(|name| name.to_string_lossy())
+        .map(|name| name.strip_suffix(".git").unwrap_or(&name).to_string())
+        .unwrap_or_else(|| "git-project".to_string())
+}
+
+/// Splits the `:path` suffix off shorthand notation like
+/// `user/repo@branch:path/to/dir`. Full URLs carry the path themselves.
+fn split_subpath(url: &str) -> (&str, Option<&str>) {
+    if url.contains("://") {
+        return (url, None);
+    }
+
+    match url.split_once(':') {
+        Some((repo, subpath)) => (repo, Some(subpath)),
+        None => (url, None),
+    }
+}
+
 impl Default for RemoteAnalyzer {
     fn default() -> Self {
         Self::new()
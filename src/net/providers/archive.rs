@@ -1,3 +1,4 @@
+use crate::net::extract::ARCHIVE_SUFFIXES;
 use crate::net::traits::{GitProvider, ParsedRepository, ProviderConfig};
 use async_trait::async_trait;
 use reqwest::Client;
@@ -22,11 +23,7 @@ impl GitProvider for ArchiveProvider {
     }
 
     fn can_handle(&self, url: &str) -> bool {
-        url.ends_with(".tar.gz")
-            || url.ends_with(".tgz")
-            || url.ends_with(".tar.bz2")
-            || url.ends_with(".tar.xz")
-            || url.ends_with(".zip")
+        ARCHIVE_SUFFIXES.iter().any(|suffix| url.ends_with(suffix))
             || url.contains("/archive/")
             || url.contains("/tarball/")
             || url.contains("/zipball/")
@@ -73,21 +70,11 @@ impl GitProvider for ArchiveProvider {
 
 impl ArchiveProvider {
     fn extract_name_from_filename(&self, filename: &str) -> String {
-        let name = if filename.ends_with(".tar.gz") {
-            filename.trim_end_matches(".tar.gz")
-        } else if filename.ends_with(".tgz") {
-            filename.trim_end_matches(".tgz")
-        } else if filename.ends_with(".tar.bz2") {
-            filename.trim_end_matches(".tar.bz2")
-        } else if filename.ends_with(".tar.xz") {
-            filename.trim_end_matches(".tar.xz")
-        } else if filename.ends_with(".zip") {
-            filename.trim_end_matches(".zip")
-        } else {
-            filename
-        };
-
-        name.to_string()
+        ARCHIVE_SUFFIXES
+            .iter()
+            .find_map(|suffix| filename.strip_suffix(suffix))
+            .unwrap_or(filename)
+            .to_string()
     }
 
     fn extract_host_from_url(&self, url: &str) -> String {
@@ -119,6 +106,8 @@ mod tests {
         assert!(provider.can_handle("https://example.com/project.tgz"));
         assert!(provider.can_handle("https://example.com/project.tar.bz2"));
         assert!(provider.can_handle("https://example.com/project.tar.xz"));
+        assert!(provider.can_handle("https://example.com/project.tar.zst"));
+        assert!(provider.can_handle("https://example.com/project.tar"));
         assert!(provider.can_handle("https://example.com/project.zip"));
         assert!(provider.can_handle("https://example.com/archive/main.tar.gz"));
         assert!(provider.can_handle("https://example.com/tarball/main"));
@@ -191,6 +180,11 @@ mod tests {
             provider.extract_name_from_filename("release.zip"),
             "release"
         );
+        assert_eq!(
+            provider.extract_name_from_filename("linux-6.9.tar.zst"),
+            "linux-6.9"
+        );
+        assert_eq!(provider.extract_name_from_filename("dist.tar"), "dist");
     }
 
     #[test]
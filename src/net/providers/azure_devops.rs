@@ -1,3 +1,4 @@
+use super::percent_decode;
 use crate::net::traits::{GitProvider, ParsedRepository, ProviderConfig};
 use async_trait::async_trait;
 use reqwest::Client;
@@ -32,15 +33,12 @@ impl GitProvider for AzureDevOpsProvider {
 
         let url = url.trim_end_matches('/');
 
-        if url.contains("?version=GB") {
-            return self.parse_branch_url(url);
+        match url.split_once('?') {
+            Some((base_url, query)) => self
+                .parse_basic_url(base_url)
+                .map(|parsed| self.parse_query(parsed, query)),
+            None => self.parse_basic_url(url),
         }
-
-        if url.contains("?version=GC") {
-            return self.parse_commit_url(url);
-        }
-
-        self.parse_basic_url(url)
     }
 
     fn build_download_urls(&self, parsed: &ParsedRepository) -> Vec<String> {
@@ -94,28 +92,30 @@ impl GitProvider for AzureDevOpsProvider {
 }
 
 impl AzureDevOpsProvider {
-    fn parse_commit_url(&self, url: &str) -> Option<ParsedRepository> {
-        if let Some(commit_start) = url.find("?version=GC") {
-            let base_url = &url[..commit_start];
-            let commit = &url[commit_start + "?version=GC".len()..];
-
-            if let Some(parsed_base) = self.parse_basic_url(base_url) {
-                return Some(parsed_base.with_commit(commit.to_string()));
+    // Repository URLs select the ref with `version=GB<branch>` or
+    // `version=GC<commit>` and the directory with `path=/<dir>`.
+    fn parse_query(&self, parsed: ParsedRepository, query: &str) -> ParsedRepository {
+        let mut parsed = parsed;
+        let mut subpath = None;
+
+        for (key, value) in query.split('&').filter_map(|param| param.split_once('=')) {
+            match key {
+                "version" => {
+                    if let Some(branch) = value.strip_prefix("GB") {
+                        parsed = parsed.with_branch(branch.to_string());
+                    } else if let Some(commit) = value.strip_prefix("GC") {
+                        parsed = parsed.with_commit(commit.to_string());
+                    }
+                }
+                "path" => subpath = Some(percent_decode(value)),
+                _ => {}
             }
         }
-        None
-    }
-
-    fn parse_branch_url(&self, url: &str) -> Option<ParsedRepository> {
-        if let Some(branch_start) = url.find("?version=GB") {
-            let base_url = &url[..branch_start];
-            let branch = &url[branch_start + "?version=GB".len()..];
 
-            if let Some(parsed_base) = self.parse_basic_url(base_url) {
-                return Some(parsed_base.with_branch(branch.to_string()));
-            }
+        match subpath {
+            Some(subpath) => parsed.with_subpath(subpath),
+            None => parsed,
         }
-        None
     }
 
     fn parse_basic_url(&self, url: &str) -> Option<ParsedRepository> {
@@ -194,4 +194,25 @@ mod tests {
         assert_eq!(parsed.branch_or_commit, Some("develop".to_string()));
         assert!(!parsed.is_commit);
     }
+
+    #[test]
+    fn test_parse_path_url() {
+        let provider = AzureDevOpsProvider::new();
+
+        let parsed = provider
+            .parse_url(
+                "https://dev.azure.com/org/mono/_git/mono?path=/services/billing&version=GBmain",
+            )
+            .unwrap();
+        assert_eq!(parsed.branch_or_commit, Some("main".to_string()));
+        assert_eq!(parsed.subpath, Some("services/billing".to_string()));
+        assert_eq!(parsed.project_name, "mono@main:services/billing");
+
+        let parsed = provider
+            .parse_url(
+                "https://dev.azure.com/org/mono/_git/mono?path=%2Fservices%2Fbilling%20api&version=GBmain",
+            )
+            .unwrap();
+        assert_eq!(parsed.subpath, Some("services/billing api".to_string()));
+    }
 }
@@ -40,6 +40,10 @@ impl GitProvider for BitbucketProvider {
             return self.parse_branch_url(url);
         }
 
+        if url.contains("/src/") {
+            return self.parse_src_url(url);
+        }
+
         self.parse_basic_url(url)
     }
 
@@ -124,6 +128,35 @@ impl BitbucketProvider {
         None
     }
 
+    fn parse_src_url(&self, url: &str) -> Option<ParsedRepository> {
+        let url = url.split('?').next().unwrap_or(url).trim_end_matches('/');
+        let parts: Vec<&str> = url.split('/').collect();
+        if let Some(src_pos) = parts.iter().position(|&x| x == "src") {
+            if src_pos + 1 < parts.len() && src_pos >= 2 {
+                let owner = parts[src_pos - 2].to_string();
+                let repo = parts[src_pos - 1].to_string();
+                let reference = parts[src_pos + 1].to_string();
+                let subpath = parts[src_pos + 2..].join("/");
+
+                // Source URLs name either a branch or a full commit hash.
+                let parsed = ParsedRepository::new(owner, repo);
+                let parsed =
+                    if reference.len() == 40 && reference.chars().all(|c| c.is_ascii_hexdigit()) {
+                        parsed.with_commit(reference)
+                    } else {
+                        parsed.with_branch(reference)
+                    };
+
+                return Some(
+                    parsed
+                        .with_host("bitbucket.org".to_string())
+                        .with_subpath(subpath),
+                );
+            }
+        }
+        None
+    }
+
     fn parse_basic_url(&self, url: &str) -> Option<ParsedRepository> {
         let parts: Vec<&str> = url.split('/').collect();
         if let Some(bitbucket_pos) = parts.iter().position(|&x| x == "bitbucket.org") {
@@ -211,4 +244,17 @@ mod tests {
         let urls = provider.build_download_urls(&parsed);
         assert!(urls.contains(&"https://bitbucket.org/user/repo/get/main.tar.gz".to_string()));
     }
+
+    #[test]
+    fn test_parse_src_url_with_subpath() {
+        let provider = BitbucketProvider::new();
+
+        let parsed = provider
+            .parse_url("https://bitbucket.org/org/mono/src/main/services/billing/")
+            .unwrap();
+        assert_eq!(parsed.owner, "org");
+        assert_eq!(parsed.repo, "mono");
+        assert_eq!(parsed.branch_or_commit, Some("main".to_string()));
+        assert_eq!(parsed.subpath, Some("services/billing".to_string()));
+    }
 }
@@ -113,11 +113,13 @@ impl CodebergProvider {
                 let owner = parts[branch_pos - 3].to_string();
                 let repo = parts[branch_pos - 2].to_string();
                 let branch = parts[branch_pos + 1].to_string();
+                let subpath = parts[branch_pos + 2..].join("/");
 
                 return Some(
                     ParsedRepository::new(owner, repo)
                         .with_branch(branch)
-                        .with_host("codeberg.org".to_string()),
+                        .with_host("codeberg.org".to_string())
+                        .with_subpath(subpath),
                 );
             }
         }
@@ -211,4 +213,16 @@ mod tests {
         let urls = provider.build_download_urls(&parsed);
         assert!(urls.contains(&"https://codeberg.org/user/repo/archive/main.tar.gz".to_string()));
     }
+
+    #[test]
+    fn test_parse_branch_url_with_subpath() {
+        let provider = CodebergProvider::new();
+
+        let parsed = provider
+            .parse_url("https://codeberg.org/org/mono/src/branch/main/services/billing")
+            .unwrap();
+        assert_eq!(parsed.branch_or_commit, Some("main".to_string()));
+        assert_eq!(parsed.subpath, Some("services/billing".to_string()));
+        assert_eq!(parsed.project_name, "mono@main:services/billing");
+    }
 }
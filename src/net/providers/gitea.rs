@@ -124,11 +124,13 @@ impl GiteaProvider {
                 let owner = parts[branch_pos - 3].to_string();
                 let repo = parts[branch_pos - 2].to_string();
                 let branch = parts[branch_pos + 1].to_string();
+                let subpath = parts[branch_pos + 2..].join("/");
 
                 return Some(
                     ParsedRepository::new(owner, repo)
                         .with_branch(branch)
-                        .with_host(host),
+                        .with_host(host)
+                        .with_subpath(subpath),
                 );
             }
         }
@@ -189,4 +191,16 @@ mod tests {
         let urls = provider.build_download_urls(&parsed);
         assert!(urls.contains(&"https://gitea.com/user/repo/archive/main.tar.gz".to_string()));
     }
+
+    #[test]
+    fn test_parse_branch_url_with_subpath() {
+        let provider = GiteaProvider::new();
+
+        let parsed = provider
+            .parse_url("https://gitea.com/org/mono/src/branch/main/services/billing")
+            .unwrap();
+        assert_eq!(parsed.owner, "org");
+        assert_eq!(parsed.branch_or_commit, Some("main".to_string()));
+        assert_eq!(parsed.subpath, Some("services/billing".to_string()));
+    }
 }
@@ -1,3 +1,4 @@
+use super::percent_decode;
 use crate::net::traits::{GitProvider, ParsedRepository, ProviderConfig};
 use async_trait::async_trait;
 use reqwest::Client;
@@ -33,6 +34,8 @@ impl GitProvider for GitHubProvider {
             return None;
         }
 
+        // The query and fragment aren't part of the repository path.
+        let url = url.split(['?', '#']).next().unwrap_or(url);
         let url = url.trim_end_matches('/');
 
         if url.contains("/tree/") {
@@ -170,11 +173,13 @@ impl GitHubProvider {
                 let owner = parts[tree_pos - 2].to_string();
                 let repo = parts[tree_pos - 1].to_string();
                 let branch = parts[tree_pos + 1].to_string();
+                let subpath = percent_decode(&parts[tree_pos + 2..].join("/"));
 
                 return Some(
                     ParsedRepository::new(owner, repo)
                         .with_branch(branch)
-                        .with_host("github.com".to_string()),
+                        .with_host("github.com".to_string())
+                        .with_subpath(subpath),
                 );
             }
         }
@@ -271,6 +276,38 @@ mod tests {
         assert_eq!(parsed.project_name, "repo@develop");
         assert_eq!(parsed.branch_or_commit, Some("develop".to_string()));
         assert!(!parsed.is_commit);
+        assert_eq!(parsed.subpath, None);
+    }
+
+    #[test]
+    fn test_parse_tree_url_with_subpath() {
+        let provider = GitHubProvider::new();
+
+        let parsed = provider
+            .parse_url("https://github.com/org/mono/tree/main/services/billing/")
+            .unwrap();
+        assert_eq!(parsed.repo, "mono");
+        assert_eq!(parsed.branch_or_commit, Some("main".to_string()));
+        assert_eq!(parsed.subpath, Some("services/billing".to_string()));
+        assert_eq!(parsed.project_name, "mono@main:services/billing");
+
+        let parsed = provider
+            .parse_url("https://github.com/org/mono/tree/deadbeef/services")
+            .unwrap();
+        assert!(!parsed.is_commit);
+        assert_eq!(parsed.branch_or_commit, Some("deadbeef".to_string()));
+        assert_eq!(parsed.subpath, Some("services".to_string()));
+
+        let parsed = provider
+            .parse_url("https://github.com/org/mono/tree/main/services/billing?plain=1#readme")
+            .unwrap();
+        assert_eq!(parsed.branch_or_commit, Some("main".to_string()));
+        assert_eq!(parsed.subpath, Some("services/billing".to_string()));
+
+        let parsed = provider
+            .parse_url("https://github.com/org/mono/tree/main/docs/user%20guide")
+            .unwrap();
+        assert_eq!(parsed.subpath, Some("docs/user guide".to_string()));
     }
 
     #[test]
@@ -46,25 +46,14 @@ impl GitProvider for GitLabProvider {
         if let Some(ref branch_or_commit) = parsed.branch_or_commit {
             let host = parsed.host.as_deref().unwrap_or("gitlab.com");
 
-            if parsed.is_commit {
-                urls.push(format!(
-                    "https://{}/{}/-/archive/{}/{}-{}.tar.gz",
-                    host,
-                    self.build_project_path(&parsed.owner, &parsed.repo),
-                    branch_or_commit,
-                    parsed.repo,
-                    branch_or_commit
-                ));
-            } else {
-                urls.push(format!(
-                    "https://{}/{}/-/archive/{}/{}-{}.tar.gz",
-                    host,
-                    self.build_project_path(&parsed.owner, &parsed.repo),
-                    branch_or_commit,
-                    parsed.repo,
-                    branch_or_commit
-                ));
-            }
+            urls.push(format!(
+                "https://{}/{}/-/archive/{}/{}-{}.tar.gz",
+                host,
+                self.build_project_path(&parsed.owner, &parsed.repo),
+                branch_or_commit,
+                parsed.repo,
+                branch_or_commit
+            ));
         }
 
         urls
@@ -107,6 +96,8 @@ impl GitProvider for GitLabProvider {
 
 impl GitLabProvider {
     fn parse_tree_url(&self, url: &str) -> Option<ParsedRepository> {
+        // Tree URLs copied from the browser often carry `?ref_type=heads`.
+        let url = url.split('?').next().unwrap_or(url).trim_end_matches('/');
         let parts: Vec<&str> = url.split('/').collect();
         if let Some(tree_pos) = parts.iter().position(|&x| x == "tree") {
             if tree_pos + 1 < parts.len() && tree_pos >= 3 {
@@ -115,11 +106,13 @@ impl GitLabProvider {
                 let owner = parts[gitlab_pos + 1].to_string();
                 let repo = parts[gitlab_pos + 2].to_string();
                 let branch = parts[tree_pos + 1].to_string();
+                let subpath = parts[tree_pos + 2..].join("/");
 
                 return Some(
                     ParsedRepository::new(owner, repo)
                         .with_branch(branch)
-                        .with_host(host),
+                        .with_host(host)
+                        .with_subpath(subpath),
                 );
             }
         }
@@ -211,6 +204,18 @@ mod tests {
         assert!(!parsed.is_commit);
     }
 
+    #[test]
+    fn test_parse_tree_url_with_subpath() {
+        let provider = GitLabProvider::new();
+
+        let parsed = provider
+            .parse_url("https://gitlab.com/org/mono/-/tree/main/services/billing?ref_type=heads")
+            .unwrap();
+        assert_eq!(parsed.branch_or_commit, Some("main".to_string()));
+        assert_eq!(parsed.subpath, Some("services/billing".to_string()));
+        assert_eq!(parsed.project_name, "mono@main:services/billing");
+    }
+
     #[test]
     fn test_build_download_urls() {
         let provider = GitLabProvider::new();
@@ -108,18 +108,19 @@ impl SourceForgeProvider {
     fn parse_tree_url(&self, url: &str) -> Option<ParsedRepository> {
         let parts: Vec<&str> = url.split('/').collect();
         if let Some(tree_pos) = parts.iter().position(|&x| x == "tree") {
-            if tree_pos + 1 < parts.len() {
-                if let Some(ci_pos) = parts.iter().position(|&x| x == "ci") {
-                    if ci_pos >= 3 {
-                        let project = parts[ci_pos - 1].to_string();
-                        let branch = parts[tree_pos + 1].to_string();
-
-                        return Some(
-                            ParsedRepository::new("sourceforge".to_string(), project)
-                                .with_branch(branch)
-                                .with_host("sourceforge.net".to_string()),
-                        );
-                    }
+            if let Some(ci_pos) = parts.iter().position(|&x| x == "ci") {
+                if ci_pos >= 3 && ci_pos + 1 < tree_pos {
+                    // `/p/<project>/code/ci/<branch>/tree/<path>`
+                    let project = parts[ci_pos - 2].to_string();
+                    let branch = parts[ci_pos + 1].to_string();
+                    let subpath = parts[tree_pos + 1..].join("/");
+
+                    return Some(
+                        ParsedRepository::new("sourceforge".to_string(), project)
+                            .with_branch(branch)
+                            .with_host("sourceforge.net".to_string())
+                            .with_subpath(subpath),
+                    );
                 }
             }
         }
@@ -188,4 +189,16 @@ mod tests {
             urls.contains(&"https://sourceforge.net/p/project/code/ci/master/tarball".to_string())
         );
     }
+
+    #[test]
+    fn test_parse_tree_url_with_subpath() {
+        let provider = SourceForgeProvider::new();
+
+        let parsed = provider
+            .parse_url("https://sourceforge.net/p/myproject/code/ci/master/tree/src/lib/")
+            .unwrap();
+        assert_eq!(parsed.repo, "myproject");
+        assert_eq!(parsed.branch_or_commit, Some("master".to_string()));
+        assert_eq!(parsed.subpath, Some("src/lib".to_string()));
+    }
 }
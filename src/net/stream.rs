@@ -1,14 +1,13 @@
+use super::extract::{self, ArchiveResults};
 use super::ProgressHook;
 use crate::core::{
-    analysis::{FileMetrics, ProjectAnalysis},
+    analysis::ProjectAnalysis,
     error::{AnalysisError, Result},
-    filter::{FilterStats, IntelligentFilter},
-    registry::LanguageRegistry,
+    filter::IntelligentFilter,
 };
-use flate2::read::GzDecoder;
 use futures_util::StreamExt;
 use std::io::{Cursor, Read};
-use tar::Archive;
+use std::path::Path;
 use tokio::sync::mpsc;
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -47,10 +46,7 @@ impl StreamReader {
                     }
                     Err(e) => {
                         let _ = tx
-                            .send(Err(std::io::Error::new(
-                                std::io::ErrorKind::Other,
-                                format!("Stream error: {}", e),
-                            )))
+                            .send(Err(std::io::Error::other(format!("Stream error: {}", e))))
                             .await;
                         break;
                     }
@@ -89,10 +85,7 @@ impl StreamReader {
                     }
                     Err(e) => {
                         let _ = tx
-                            .send(Err(std::io::Error::new(
-                                std::io::ErrorKind::Other,
-                                format!("Stream error: {}", e),
-                            )))
+                            .send(Err(std::io::Error::other(format!("Stream error: {}", e))))
                             .await;
                         break;
                     }
@@ -173,42 +166,20 @@ impl Read for StreamReader {
     }
 }
 
+/// Analyzes a downloaded archive. Despite the name, any format recognised by
+/// [`extract::ArchiveFormat`] is accepted.
 pub async fn process_tarball(
     bytes: bytes::Bytes,
     project_analysis: &mut ProjectAnalysis,
     filter: &IntelligentFilter,
     _progress_hook: &dyn ProgressHook,
 ) -> Result<()> {
-    let decoder = GzDecoder::new(Cursor::new(bytes));
-    let mut archive = Archive::new(decoder);
-
-    let entries = archive
-        .entries()
-        .map_err(|e| AnalysisError::archive(format!("Failed to read tar entries: {}", e)))?;
-
-    let mut stats = FilterStats::new();
-
-    for entry in entries {
-        let entry = entry
-            .map_err(|e| AnalysisError::archive(format!("Failed to read tar entry: {}", e)))?;
-
-        if let Ok(metrics) = process_tar_entry_sync(entry, filter, &mut stats) {
-            project_analysis.add_file_metrics(metrics)?;
-        }
-    }
-
-    #[cfg(feature = "cli")]
-    log::info!(
-        "Filter stats: processed {}/{} files ({:.1}% filtered), saved {}",
-        stats.processed,
-        stats.total_entries,
-        stats.filter_ratio() * 100.0,
-        stats.format_bytes_saved()
-    );
-
-    Ok(())
+    let results = extract::analyze_buffered_sync(bytes, filter)?;
+    add_archive_results(project_analysis, results)
 }
 
+/// Analyzes an archive as it downloads, choosing the decoder from its first
+/// bytes.
 pub async fn process_tarball_stream(
     stream_reader: StreamReader,
     project_analysis: &mut ProjectAnalysis,
@@ -216,189 +187,82 @@ pub async fn process_tarball_stream(
     _progress_hook: &dyn ProgressHook,
 ) -> Result<()> {
     #[cfg(not(target_arch = "wasm32"))]
-    {
+    let results = {
         let filter = filter.clone();
-        let metrics_result = task::spawn_blocking(move || {
-            let decoder = GzDecoder::new(stream_reader);
-            let mut archive = Archive::new(decoder);
-
-            let entries = archive.entries().map_err(|e| {
-                AnalysisError::archive(format!("Failed to read tar entries: {}", e))
-            })?;
-
-            let mut collected_metrics = Vec::new();
-            let mut stats = FilterStats::new();
-
-            for entry in entries {
-                let entry = entry.map_err(|e| {
-                    AnalysisError::archive(format!("Failed to read tar entry: {}", e))
-                })?;
-
-                if let Ok(metrics) = process_tar_entry_sync(entry, &filter, &mut stats) {
-                    collected_metrics.push(metrics);
-                }
-            }
-
-            #[cfg(feature = "cli")]
-            log::info!(
-                "Filter stats: processed {}/{} files ({:.1}% filtered), saved {}",
-                stats.processed,
-                stats.total_entries,
-                stats.filter_ratio() * 100.0,
-                stats.format_bytes_saved()
-            );
-
-            Ok::<Vec<FileMetrics>, AnalysisError>(collected_metrics)
-        })
-        .await
-        .map_err(|e| AnalysisError::archive(format!("Task join error: {}", e)))??;
-
-        for metrics in metrics_result {
-            project_analysis.add_file_metrics(metrics)?;
-        }
-    }
+        task::spawn_blocking(move || extract::analyze_stream_sync(stream_reader, &filter))
+            .await
+            .map_err(|e| AnalysisError::archive(format!("Task join error: {}", e)))??
+    };
 
     #[cfg(target_arch = "wasm32")]
-    {
-        let decoder = GzDecoder::new(stream_reader);
-        let mut archive = Archive::new(decoder);
-
-        let entries = archive
-            .entries()
-            .map_err(|e| AnalysisError::archive(format!("Failed to read tar entries: {}", e)))?;
-
-        let mut stats = FilterStats::new();
+    let results = extract::analyze_stream_sync(stream_reader, filter)?;
 
-        for entry in entries {
-            let entry = entry
-                .map_err(|e| AnalysisError::archive(format!("Failed to read tar entry: {}", e)))?;
-
-            if let Ok(metrics) = process_tar_entry_sync(entry, filter, &mut stats) {
-                project_analysis.add_file_metrics(metrics)?;
-            }
-        }
-    }
-
-    Ok(())
+    add_archive_results(project_analysis, results)
 }
 
-fn process_tar_entry_sync<R: Read>(
-    mut entry: tar::Entry<'_, R>,
+/// Analyzes an archive from any reader, such as a file or standard input,
+/// without buffering it first.
+pub fn process_archive_reader<R: Read>(
+    reader: R,
+    project_analysis: &mut ProjectAnalysis,
     filter: &IntelligentFilter,
-    stats: &mut FilterStats,
-) -> Result<FileMetrics> {
-    let header = entry.header();
-    let path = header
-        .path()
-        .map_err(|e| AnalysisError::archive(format!("Invalid path in tar entry: {}", e)))?;
-
-    let file_path = path.to_string_lossy().to_string();
-
-    if !header.entry_type().is_file() || header.size().unwrap_or(0) == 0 {
-        return Err(AnalysisError::archive("Not a file or empty".to_string()));
-    }
-
-    let file_size = header.size().unwrap_or(0);
-
-    let should_process = filter.should_process_file(&file_path, file_size);
-    stats.record_entry(file_size, !should_process);
-
-    if !should_process {
-        return Err(AnalysisError::archive("File filtered out".to_string()));
-    }
-
-    let language = LanguageRegistry::detect_by_path(&file_path)
-        .map(|l| l.name.clone())
-        .unwrap_or_else(|| "Text".to_string());
-
-    let mut content = String::new();
-    if entry.read_to_string(&mut content).is_err() {
-        return Err(AnalysisError::archive(
-            "Failed to read file content".to_string(),
-        ));
-    }
-
-    analyze_file_content(&file_path, &content, &language, file_size)
+) -> Result<()> {
+    let results = extract::analyze_stream_sync(reader, filter)?;
+    add_archive_results(project_analysis, results)
 }
 
-fn analyze_file_content(
-    file_path: &str,
-    content: &str,
-    language: &str,
-    file_size: u64,
-) -> Result<FileMetrics> {
-    let lines: Vec<&str> = content.lines().collect();
-    let total_lines = lines.len();
-
-    let mut code_lines = 0;
-    let mut comment_lines = 0;
-    let mut blank_lines = 0;
-
-    let lang_def = LanguageRegistry::get_language(language);
-    let empty_line_comments = vec![];
-    let empty_multi_line_comments = vec![];
-    let line_comments = lang_def
-        .map(|l| &l.line_comments)
-        .unwrap_or(&empty_line_comments);
-    let multi_line_comments = lang_def
-        .map(|l| &l.multi_line_comments)
-        .unwrap_or(&empty_multi_line_comments);
-
-    let mut in_multi_line_comment = false;
-
-    for line in lines {
-        let trimmed = line.trim();
-
-        if trimmed.is_empty() {
-            blank_lines += 1;
-            continue;
-        }
+/// Analyzes a local directory, or an archive file on disk.
+pub fn process_local_path(
+    path: &Path,
+    project_analysis: &mut ProjectAnalysis,
+    filter: &IntelligentFilter,
+) -> Result<()> {
+    let results = if path.is_dir() {
+        extract::analyze_directory_sync(path, filter)?
+    } else {
+        extract::analyze_file_sync(path, filter)?
+    };
+    add_archive_results(project_analysis, results)
+}
 
-        let mut is_comment = false;
+/// Analyzes the tree of a revision in a local git repository.
+pub fn process_git_revision(
+    git_dir: &Path,
+    rev: &str,
+    project_analysis: &mut ProjectAnalysis,
+    filter: &IntelligentFilter,
+) -> Result<()> {
+    let results = extract::analyze_git_sync(git_dir, rev, filter)?;
+    add_archive_results(project_analysis, results)
+}
 
-        if !in_multi_line_comment {
-            for comment_start in line_comments {
-                if trimmed.starts_with(comment_start) {
-                    is_comment = true;
-                    break;
-                }
-            }
+fn add_archive_results(
+    project_analysis: &mut ProjectAnalysis,
+    results: ArchiveResults,
+) -> Result<()> {
+    let ArchiveResults {
+        metrics,
+        mut stats,
+        root,
+    } = results;
 
-            for (start, end) in multi_line_comments {
-                if trimmed.starts_with(start) {
-                    is_comment = true;
-                    if !trimmed.ends_with(end) {
-                        in_multi_line_comment = true;
-                    }
-                    break;
-                }
-            }
-        } else {
-            is_comment = true;
-            for (_, end) in multi_line_comments {
-                if trimmed.ends_with(end) {
-                    in_multi_line_comment = false;
-                    break;
-                }
-            }
-        }
+    #[cfg(feature = "cli")]
+    log::info!(
+        "Filter stats: processed {}/{} files ({:.1}% filtered), saved {}",
+        stats.processed,
+        stats.total_entries,
+        stats.filter_ratio() * 100.0,
+        stats.format_bytes_saved()
+    );
 
-        if is_comment {
-            comment_lines += 1;
-        } else {
-            code_lines += 1;
-        }
+    for file_metrics in metrics {
+        project_analysis.add_file_metrics(file_metrics)?;
     }
+    project_analysis.archive_root = root;
+    project_analysis.filter_stats.merge(&stats);
+    project_analysis
+        .filter_decisions
+        .append(&mut stats.decisions);
 
-    let metrics = FileMetrics::new(
-        file_path,
-        language.to_string(),
-        total_lines,
-        code_lines,
-        comment_lines,
-        blank_lines,
-    )?
-    .with_size_bytes(file_size);
-
-    Ok(metrics)
+    Ok(())
 }
@@ -192,6 +192,9 @@ pub struct ParsedRepository {
 
     /// Host name (e.g., "github.com")
     pub host: Option<String>,
+
+    /// Directory inside the repository to analyze (if specified)
+    pub subpath: Option<String>,
 }
 
 impl ParsedRepository {
@@ -209,6 +212,7 @@ impl ParsedRepository {
             is_commit: false,
             project_name,
             host: None,
+            subpath: None,
         }
     }
 
@@ -243,6 +247,19 @@ impl ParsedRepository {
         self.host = Some(host);
         self
     }
+
+    /// Limit the analysis to a directory and update project name
+    ///
+    /// # Arguments
+    /// * `subpath` - Directory relative to the repository root
+    pub fn with_subpath(mut self, subpath: String) -> Self {
+        let subpath = subpath.trim_matches('/');
+        if !subpath.is_empty() {
+            self.project_name = format!("{}:{}", self.project_name, subpath);
+            self.subpath = Some(subpath.to_string());
+        }
+        self
+    }
 }
 
 /// Git provider trait for handling different repository hosting services
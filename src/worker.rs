@@ -26,6 +26,8 @@ pub struct AnalysisOptions {
     pub ignore_hidden: bool,
     pub aggressive_filtering: Option<bool>,
     pub custom_filter: Option<IntelligentFilter>,
+    #[serde(default)]
+    pub directory_depth: Option<usize>,
 }
 
 impl Default for AnalysisOptions {
@@ -44,6 +46,7 @@ impl Default for AnalysisOptions {
             ignore_hidden: true,
             aggressive_filtering: None,
             custom_filter: None,
+            directory_depth: None,
         }
     }
 }
@@ -61,6 +64,8 @@ struct WASMAnalysisResult {
     project_name: String,
     summary: crate::core::analysis::ProjectSummary,
     language_statistics: Vec<crate::core::analysis::LanguageStatistics>,
+    filter_stats: crate::core::filter::FilterStats,
+    directory_tree: crate::core::analysis::DirectoryNode,
     debug_info: WASMDebugInfo,
 }
 
@@ -127,6 +132,8 @@ fn create_wasm_result(
         project_name: analysis.project_name.clone(),
         summary: analysis.get_summary(),
         language_statistics: analysis.get_language_statistics(),
+        filter_stats: analysis.filter_stats.clone(),
+        directory_tree: analysis.directory_tree.clone(),
         debug_info: WASMDebugInfo {
             total_languages: analysis.language_analyses.len(),
             total_files: analysis.global_metrics.file_count,
@@ -171,6 +178,10 @@ pub async fn analyze_url(url: String, options: JsValue) -> Result<JsValue, JsVal
         },
     }
 
+    if let Some(depth) = opts.directory_depth {
+        analyzer.set_directory_depth(depth);
+    }
+
     let start_time = Instant::now();
     let result = match analyzer.analyze_url(&url).await {
         Ok(analysis) => {
@@ -1,4 +1,7 @@
-use bytes_radar::{AggregateMetrics, FileMetrics, LanguageAnalysis, Result};
+use bytes_radar::{
+    AggregateMetrics, EmbeddedMetrics, FileMetrics, LanguageAnalysis, LanguageRegistry,
+    LineCounter, Result,
+};
 
 #[cfg(test)]
 mod analysis_tests {
@@ -227,4 +230,571 @@ mod analysis_tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_line_counter_comment_marker_inside_string() {
+        let rust = LanguageRegistry::get_language("Rust");
+        let content = "let s = \"/* not a comment\";\nlet t = 1;\n// real comment\n";
+
+        let counts = LineCounter::count(content, rust);
+
+        assert_eq!(counts.total_lines, 3);
+        assert_eq!(counts.code_lines, 2);
+        assert_eq!(counts.comment_lines, 1);
+    }
+
+    #[test]
+    fn test_line_counter_inline_block_comment() {
+        let cpp = LanguageRegistry::get_language("C++");
+        let content = "x = 1 /* c */ + 2;\n/* only a comment */\n/* comment */ y = 3;\n";
+
+        let counts = LineCounter::count(content, cpp);
+
+        assert_eq!(counts.code_lines, 2);
+        assert_eq!(counts.comment_lines, 1);
+    }
+
+    #[test]
+    fn test_line_counter_multi_line_comment_and_blank() {
+        let c = LanguageRegistry::get_language("C");
+        let content = "int a;\n/*\n * doc\n\n */\nint b; /* trailing\n still comment */\n";
+
+        let counts = LineCounter::count(content, c);
+
+        assert_eq!(counts.total_lines, 7);
+        assert_eq!(counts.code_lines, 2);
+        assert_eq!(counts.comment_lines, 4);
+        assert_eq!(counts.blank_lines, 1);
+    }
+
+    #[test]
+    fn test_line_counter_multi_line_string() {
+        let js = LanguageRegistry::get_language("JavaScript");
+        let content = "const s = `\n// inside template\n/* still text`;\n// comment\n";
+
+        let counts = LineCounter::count(content, js);
+
+        assert_eq!(counts.code_lines, 3);
+        assert_eq!(counts.comment_lines, 1);
+    }
+
+    #[test]
+    fn test_line_counter_escapes_and_verbatim_strings() {
+        let rust = LanguageRegistry::get_language("Rust");
+        let content = "let a = \"quote \\\" // not a comment\";\nlet b = r#\"raw \\\"#;\nlet c = '\"';\n// comment\n";
+
+        let counts = LineCounter::count(content, rust);
+
+        assert_eq!(counts.code_lines, 3);
+        assert_eq!(counts.comment_lines, 1);
+    }
+
+    #[test]
+    fn test_line_counter_unknown_language() {
+        let counts = LineCounter::count("hello\n\n# world\n", None);
+
+        assert_eq!(counts.total_lines, 3);
+        assert_eq!(counts.code_lines, 2);
+        assert_eq!(counts.blank_lines, 1);
+    }
+
+    fn assert_nested_comment(language: &str, start: &str, end: &str) {
+        let definition = LanguageRegistry::get_language(language);
+        assert!(definition.is_some(), "Unknown language: {}", language);
+
+        let mut content = format!(
+            "{start} outer\n{start} inner {end}\nstill comment\n{end}\nx\n",
+            start = start,
+            end = end
+        );
+        if definition.is_some_and(|lang| lang.is_literate) {
+            content = content
+                .lines()
+                .map(|line| format!("> {}\n", line))
+                .collect();
+        }
+        let counts = LineCounter::count(&content, definition);
+
+        assert_eq!(counts.comment_lines, 4, "Nested comment in {}", language);
+        assert_eq!(counts.code_lines, 1, "Nested comment in {}", language);
+    }
+
+    #[test]
+    fn test_nested_comments_rust() {
+        assert_nested_comment("Rust", "/*", "*/");
+
+        let counts = LineCounter::count(
+            "/* a /* b */ c */\nlet x = 1;\n",
+            LanguageRegistry::get_language("Rust"),
+        );
+        assert_eq!(counts.comment_lines, 1);
+        assert_eq!(counts.code_lines, 1);
+    }
+
+    #[test]
+    fn test_nested_comments_haskell() {
+        assert_nested_comment("Haskell", "{-", "-}");
+    }
+
+    #[test]
+    fn test_nested_comments_d() {
+        assert_nested_comment("D", "/+", "+/");
+
+        // `/* */` does not nest in D, and `/*` inside `/+ +/` is plain text.
+        let d = LanguageRegistry::get_language("D");
+        let counts = LineCounter::count("/* a /* b */\nint x;\n/+ a /* b +/\nint y;\n", d);
+        assert_eq!(counts.comment_lines, 2);
+        assert_eq!(counts.code_lines, 2);
+    }
+
+    #[test]
+    fn test_nested_comments_swift() {
+        assert_nested_comment("Swift", "/*", "*/");
+    }
+
+    #[test]
+    fn test_nested_comments_ocaml() {
+        assert_nested_comment("OCaml", "(*", "*)");
+    }
+
+    #[test]
+    fn test_nested_comments_all_nested_languages() {
+        for language in LanguageRegistry::all_languages() {
+            if language.is_nested {
+                for (start, end) in &language.multi_line_comments {
+                    assert_nested_comment(&language.name, start, end);
+                }
+            }
+
+            for (start, end) in &language.nested_comments {
+                assert_nested_comment(&language.name, start, end);
+            }
+        }
+    }
+
+    #[test]
+    fn test_non_nested_comments_close_at_first_marker() {
+        let c = LanguageRegistry::get_language("C");
+        let counts = LineCounter::count("/* a /* b */\nint x; /* c */\n", c);
+
+        assert_eq!(counts.comment_lines, 1);
+        assert_eq!(counts.code_lines, 1);
+    }
+
+    #[test]
+    fn test_doc_comments_rust() {
+        let rust = LanguageRegistry::get_language("Rust");
+        let content = "//! Crate docs\n/// Item docs\n//// separator\n// plain\n/**\n * Block docs\n */\n/**/\nfn main() {} /// trailing\n";
+
+        let counts = LineCounter::count(content, rust);
+
+        assert_eq!(counts.total_lines, 9);
+        assert_eq!(counts.code_lines, 1);
+        assert_eq!(counts.comment_lines, 8);
+        assert_eq!(counts.doc_comment_lines, 5);
+    }
+
+    #[test]
+    fn test_doc_quotes_python() {
+        let python = LanguageRegistry::get_language("Python");
+        let content = "def f():\n    \"\"\"Docstring.\n\n    More docs.\n    \"\"\"\n    sql = \"\"\"\n    SELECT 1\n    \"\"\"\n    # comment\n";
+
+        let counts = LineCounter::count(content, python);
+
+        assert_eq!(counts.total_lines, 9);
+        assert_eq!(counts.blank_lines, 1);
+        assert_eq!(counts.code_lines, 4);
+        assert_eq!(counts.comment_lines, 4);
+        assert_eq!(counts.doc_comment_lines, 3);
+    }
+
+    #[test]
+    fn test_doc_comment_lines_aggregation() -> Result<()> {
+        let mut analysis = LanguageAnalysis::new("Rust".to_string());
+
+        analysis.add_file_metrics(
+            FileMetrics::new("lib.rs", "Rust".to_string(), 50, 30, 15, 5)?
+                .with_doc_comment_lines(10),
+        )?;
+        analysis.add_file_metrics(
+            FileMetrics::new("main.rs", "Rust".to_string(), 20, 15, 3, 2)?
+                .with_doc_comment_lines(1),
+        )?;
+
+        let stats = analysis.calculate_statistics();
+        assert_eq!(analysis.aggregate_metrics.doc_comment_lines, 11);
+        assert_eq!(stats.comment_lines, 18);
+        assert_eq!(stats.doc_comment_lines, 11);
+
+        let invalid =
+            FileMetrics::new("bad.rs", "Rust".to_string(), 10, 8, 1, 1)?.with_doc_comment_lines(2);
+        assert!(invalid.validate().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_line_comment_position_start_fortran() {
+        let fortran = LanguageRegistry::get_language("FORTRAN Legacy");
+        let content = "C     upper comment\nc     lower comment\n* star comment\n      CALL FOO()\n      X = Y * 2\n      Z = 1 ! inline\n";
+
+        let counts = LineCounter::count(content, fortran);
+
+        assert_eq!(counts.comment_lines, 3);
+        assert_eq!(counts.code_lines, 3);
+    }
+
+    #[test]
+    fn test_line_comment_position_start_spice() {
+        let spice = LanguageRegistry::get_language("Spice Netlist");
+        let content = "* RC filter\nR1 in out 1k\n  * not in first column\n";
+
+        let counts = LineCounter::count(content, spice);
+
+        assert_eq!(counts.comment_lines, 1);
+        assert_eq!(counts.code_lines, 2);
+    }
+
+    #[test]
+    fn test_case_insensitive_line_comments() {
+        let batch = LanguageRegistry::get_language("Batch");
+        let content = "@echo off\nrem lower\nREM upper\nRem mixed\n:: label comment\n";
+
+        let counts = LineCounter::count(content, batch);
+
+        assert_eq!(counts.comment_lines, 4);
+        assert_eq!(counts.code_lines, 1);
+    }
+
+    #[test]
+    fn test_word_comment_markers_need_a_word_boundary() {
+        let batch = LanguageRegistry::get_language("Batch");
+        let content = "rem\nREM: note\nRemove-Item x\nremainder = 1\nrem_count=2\n";
+        let counts = LineCounter::count(content, batch);
+        assert_eq!(counts.comment_lines, 2);
+        assert_eq!(counts.code_lines, 3);
+
+        let vbscript = LanguageRegistry::get_language("VBScript");
+        let content = "Rem setup\nRemote = True\n' note\n";
+        let counts = LineCounter::count(content, vbscript);
+        assert_eq!(counts.comment_lines, 2);
+        assert_eq!(counts.code_lines, 1);
+
+        let fortran = LanguageRegistry::get_language("FORTRAN Legacy");
+        let content = "cThis is a comment\n      x = 1\n";
+        let counts = LineCounter::count(content, fortran);
+        assert_eq!(counts.comment_lines, 1);
+        assert_eq!(counts.code_lines, 1);
+    }
+
+    fn embedded_lines<'a>(embedded: &'a [EmbeddedMetrics], language: &str) -> &'a EmbeddedMetrics {
+        embedded
+            .iter()
+            .find(|e| e.language == language)
+            .unwrap_or_else(|| panic!("No embedded {} region", language))
+    }
+
+    #[test]
+    fn test_embedded_vue_regions() {
+        let vue = LanguageRegistry::get_language("Vue");
+        let content = "<template>\n  <div>{{ msg }}</div>\n  <!-- note -->\n</template>\n\n<script lang=\"ts\">\n// setup\nexport default {}\n</script>\n\n<style lang=\"scss\">\n.a { color: red; }\n</style>\n";
+
+        let (counts, embedded) = LineCounter::count_with_embedded(content, vue);
+
+        assert_eq!(counts.total_lines, 13);
+        assert_eq!(counts.blank_lines, 2);
+
+        let html = embedded_lines(&embedded, "HTML");
+        assert_eq!(html.lines.code_lines, 1);
+        assert_eq!(html.lines.comment_lines, 1);
+
+        let ts = embedded_lines(&embedded, "TypeScript");
+        assert_eq!(ts.lines.code_lines, 1);
+        assert_eq!(ts.lines.comment_lines, 1);
+
+        let scss = embedded_lines(&embedded, "Sass");
+        assert_eq!(scss.lines.code_lines, 1);
+    }
+
+    #[test]
+    fn test_embedded_html_script_defaults() {
+        let html = LanguageRegistry::get_language("HTML");
+        let content = "<html>\n<script src=\"app.js\"></script>\n<script>\n  let a = 1; // note\n  // comment\n</script>\n</html>\n";
+
+        let (counts, embedded) = LineCounter::count_with_embedded(content, html);
+
+        assert_eq!(counts.total_lines, 7);
+        assert_eq!(embedded.len(), 1);
+
+        let js = embedded_lines(&embedded, "JavaScript");
+        assert_eq!(js.lines.total_lines, 2);
+        assert_eq!(js.lines.code_lines, 1);
+        assert_eq!(js.lines.comment_lines, 1);
+    }
+
+    #[test]
+    fn test_embedded_markdown_code_fences() {
+        let markdown = LanguageRegistry::get_language("Markdown");
+        let content = "# Title\n\n```rust\n// comment\nfn main() {}\n```\n\n```\nplain block\n```\n\n~~~python\nprint(1)\n~~~\n";
+
+        let (counts, embedded) = LineCounter::count_with_embedded(content, markdown);
+
+        assert_eq!(counts.total_lines, 14);
+        assert_eq!(embedded.len(), 2);
+
+        let rust = embedded_lines(&embedded, "Rust");
+        assert_eq!(rust.lines.code_lines, 1);
+        assert_eq!(rust.lines.comment_lines, 1);
+
+        let python = embedded_lines(&embedded, "Python");
+        assert_eq!(python.lines.code_lines, 1);
+    }
+
+    #[test]
+    fn test_embedded_astro_frontmatter() {
+        let astro = LanguageRegistry::get_language("Astro");
+        let content = "---\nconst title = \"Hi\";\n---\n<h1>{title}</h1>\n";
+
+        let (_, embedded) = LineCounter::count_with_embedded(content, astro);
+
+        assert_eq!(embedded_lines(&embedded, "TypeScript").lines.code_lines, 1);
+    }
+
+    #[test]
+    fn test_embedded_statistics_aggregation() -> Result<()> {
+        let mut analysis = LanguageAnalysis::new("Vue".to_string());
+        let vue = LanguageRegistry::get_language("Vue");
+
+        for name in ["a.vue", "b.vue"] {
+            let content = "<script>\nlet x = 1;\n</script>\n";
+            let (counts, embedded) = LineCounter::count_with_embedded(content, vue);
+            analysis.add_file_metrics(
+                FileMetrics::new(
+                    name,
+                    "Vue".to_string(),
+                    counts.total_lines,
+                    counts.code_lines,
+                    counts.comment_lines,
+                    counts.blank_lines,
+                )?
+                .with_embedded(embedded),
+            )?;
+        }
+
+        let stats = analysis.calculate_statistics();
+        assert_eq!(stats.total_lines, 6);
+        assert_eq!(stats.embedded.len(), 1);
+        assert_eq!(stats.embedded[0].language, "JavaScript");
+        assert_eq!(stats.embedded[0].lines.code_lines, 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_literate_haskell() {
+        let lhs = LanguageRegistry::detect_by_extension("lhs");
+        assert_eq!(lhs.map(|lang| lang.name.as_str()), Some("Literate Haskell"));
+
+        let counts = LineCounter::count(include_str!("fixtures/literate/bird.lhs"), lhs);
+
+        assert_eq!(counts.total_lines, 18);
+        assert_eq!(counts.blank_lines, 5);
+        assert_eq!(counts.code_lines, 5);
+        assert_eq!(counts.comment_lines, 8);
+    }
+
+    #[test]
+    fn test_literate_coffeescript() {
+        let litcoffee = LanguageRegistry::detect_by_extension("litcoffee");
+        assert_eq!(
+            litcoffee.map(|lang| lang.name.as_str()),
+            Some("Literate CoffeeScript")
+        );
+
+        let counts = LineCounter::count(
+            include_str!("fixtures/literate/greeter.litcoffee"),
+            litcoffee,
+        );
+
+        assert_eq!(counts.total_lines, 15);
+        assert_eq!(counts.blank_lines, 5);
+        assert_eq!(counts.code_lines, 3);
+        assert_eq!(counts.comment_lines, 7);
+
+        // Bird tracks are Literate Haskell syntax, not Markdown.
+        let counts = LineCounter::count("> greet = 1\n", litcoffee);
+        assert_eq!(counts.code_lines, 0);
+        assert_eq!(counts.comment_lines, 1);
+    }
+
+    #[test]
+    fn test_literate_prose_formats_unchanged() {
+        let markdown = LanguageRegistry::get_language("Markdown");
+        let counts = LineCounter::count("# Title\n\n> quoted text\n", markdown);
+
+        assert_eq!(counts.code_lines, 2);
+        assert_eq!(counts.comment_lines, 0);
+    }
+
+    fn detected_name(path: &str, content: &str) -> Option<String> {
+        LanguageRegistry::detect_by_content(path, content.as_bytes()).map(|lang| lang.name.clone())
+    }
+
+    #[test]
+    fn test_detect_by_content_shebangs() {
+        assert_eq!(
+            detected_name("bin/deploy", "#!/usr/bin/env python3\nprint(1)\n").as_deref(),
+            Some("Python")
+        );
+        assert_eq!(
+            detected_name("bin/run", "#!/usr/bin/env node\n").as_deref(),
+            Some("JavaScript")
+        );
+        assert_eq!(
+            detected_name("bin/setup", "#!/bin/bash\nset -e\n").as_deref(),
+            Some("BASH")
+        );
+        assert_eq!(
+            detected_name("bin/tool", "#!/usr/bin/env -S perl -w\n").as_deref(),
+            Some("Perl")
+        );
+        assert_eq!(
+            detected_name("bin/py", "#!/usr/local/bin/python3.12\r\n").as_deref(),
+            Some("Python")
+        );
+    }
+
+    #[test]
+    fn test_detect_by_content_prefers_path() {
+        assert_eq!(
+            detected_name("src/main.rs", "#!/usr/bin/env python3\n").as_deref(),
+            Some("Rust")
+        );
+        assert_eq!(detected_name("bin/notes", "just some text\n"), None);
+        assert_eq!(detected_name("bin/odd", "#!/usr/bin/env\n"), None);
+    }
+
+    #[test]
+    fn test_detect_by_modeline() {
+        assert_eq!(
+            detected_name("config/setup", "# vim: set ft=ruby:\nputs 1\n").as_deref(),
+            Some("Ruby")
+        );
+        assert_eq!(
+            detected_name("tools/build.txt", "# -*- mode: python; coding: utf-8 -*-\n").as_deref(),
+            Some("Python")
+        );
+        assert_eq!(
+            detected_name("init", ";; -*- emacs-lisp -*-\n").as_deref(),
+            Some("Emacs Lisp")
+        );
+        assert_eq!(
+            detected_name("lib/helpers.inc", "x = 1\n\n\n\n\n\n\n// vim:ft=cpp\n").as_deref(),
+            Some("C++")
+        );
+    }
+
+    #[test]
+    fn test_detect_by_modeline_ignores_middle_and_unknown() {
+        let mut content = String::from("fn main() {}\n");
+        content.push_str(&"\n".repeat(10));
+        content.push_str("// vim: ft=python\n");
+        content.push_str(&"\n".repeat(10));
+
+        assert_eq!(
+            detected_name("src/main.rs", &content).as_deref(),
+            Some("Rust")
+        );
+        assert_eq!(
+            detected_name("src/main.rs", "// vim: ft=nosuchlang\n").as_deref(),
+            Some("Rust")
+        );
+        assert_eq!(
+            detected_name("src/main.rs", "// reviewed: ft=python\n").as_deref(),
+            Some("Rust")
+        );
+    }
+
+    fn detection(path: &str, content: &str) -> (Option<String>, Vec<String>) {
+        let detection = LanguageRegistry::detect(path, content.as_bytes());
+        (
+            detection.language.map(|lang| lang.name.clone()),
+            detection
+                .ambiguous_with
+                .iter()
+                .map(|lang| lang.name.clone())
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_shared_extension_defaults_are_deterministic() {
+        for _ in 0..3 {
+            assert_eq!(
+                LanguageRegistry::detect_by_extension("m").map(|l| l.name.as_str()),
+                Some("Objective-C")
+            );
+            assert_eq!(
+                LanguageRegistry::detect_by_extension("e").map(|l| l.name.as_str()),
+                Some("Phix")
+            );
+        }
+
+        let candidates: Vec<&str> = LanguageRegistry::extension_candidates("m")
+            .iter()
+            .map(|l| l.name.as_str())
+            .collect();
+        assert_eq!(candidates, vec!["Objective-C", "MATLAB"]);
+    }
+
+    #[test]
+    fn test_heuristics_resolve_shared_extensions() {
+        let cases = [
+            ("a.m", "function y = f(x)\n  y = x * 2;\nend\n", "MATLAB"),
+            (
+                "a.m",
+                "#import \"Foo.h\"\n@implementation Foo\n@end\n",
+                "Objective-C",
+            ),
+            ("a.e", "<'\nstruct packet {};\n'>\n", "Specman e"),
+            ("a.e", "puts(1, \"hello\")\n", "Phix"),
+        ];
+
+        for (path, content, expected) in cases {
+            let (language, ambiguous) = detection(path, content);
+            assert_eq!(language.as_deref(), Some(expected), "{}: {}", path, content);
+            assert!(ambiguous.is_empty(), "{}: {}", path, content);
+        }
+    }
+
+    #[test]
+    fn test_heuristics_keep_single_owner_extensions() {
+        let cases = [
+            ("a.h", "#include <vector>\nclass Foo {};\n", "C Header"),
+            ("a.pl", "parent(tom, bob).\n", "Perl"),
+            ("a.v", "Definition one := 1.\n", "Coq"),
+            (
+                "a.ts",
+                "<?xml version=\"1.0\"?>\n<TS version=\"2.1\">\n",
+                "TypeScript",
+            ),
+        ];
+
+        for (path, content, expected) in cases {
+            let (language, ambiguous) = detection(path, content);
+            assert_eq!(language.as_deref(), Some(expected), "{}: {}", path, content);
+            assert!(ambiguous.is_empty(), "{}: {}", path, content);
+        }
+    }
+
+    #[test]
+    fn test_heuristics_ambiguous_fallback() {
+        let (language, ambiguous) = detection("defs.m", "x = 1;\n");
+        assert_eq!(language.as_deref(), Some("Objective-C"));
+        assert_eq!(ambiguous, vec!["MATLAB"]);
+
+        let (language, ambiguous) = detection("main.rs", "fn main() {}\n");
+        assert_eq!(language.as_deref(), Some("Rust"));
+        assert!(ambiguous.is_empty());
+    }
 }
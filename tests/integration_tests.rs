@@ -1,3 +1,11 @@
+use bytes_radar::attributes::GitAttributes;
+use bytes_radar::attributes::LinguistAttributes;
+use bytes_radar::filter::{FilterDecision, IntelligentFilter};
+use bytes_radar::glob::{glob_match, path_match};
+use bytes_radar::ignore::IgnoreRules;
+use bytes_radar::net::extract::ArchiveFormat;
+use bytes_radar::net::stream::{process_archive_reader, process_tarball};
+use bytes_radar::net::traits::NoOpProgressHook;
 use bytes_radar::{FileCategory, FileMetrics, ProjectAnalysis, RemoteAnalyzer, Result};
 
 #[cfg(test)]
@@ -184,4 +192,1198 @@ mod integration_tests {
 
         Ok(())
     }
+
+    fn build_tarball(files: &[(&str, &str)]) -> bytes::Bytes {
+        use std::io::Write;
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&build_tar(files)).unwrap();
+        bytes::Bytes::from(encoder.finish().unwrap())
+    }
+
+    fn build_tar<C: AsRef<[u8]>>(files: &[(&str, C)]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+
+        for (path, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.as_ref().len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, path, content.as_ref())
+                .unwrap();
+        }
+
+        builder.into_inner().unwrap()
+    }
+
+    async fn analyze_tarball(files: &[(&str, &str)]) -> Result<ProjectAnalysis> {
+        analyze_tarball_with(files, &IntelligentFilter::default()).await
+    }
+
+    async fn analyze_tarball_with(
+        files: &[(&str, &str)],
+        filter: &IntelligentFilter,
+    ) -> Result<ProjectAnalysis> {
+        let mut project = ProjectAnalysis::new("tarball");
+        process_tarball(
+            build_tarball(files),
+            &mut project,
+            filter,
+            &NoOpProgressHook,
+        )
+        .await?;
+        Ok(project)
+    }
+
+    fn analyzed_paths(project: &ProjectAnalysis) -> Vec<String> {
+        let mut paths: Vec<String> = project
+            .language_analyses
+            .values()
+            .flat_map(|analysis| analysis.file_metrics.iter().map(|f| f.file_path.clone()))
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn test_gitattributes_parsing() {
+        let mut attributes = GitAttributes::new();
+        attributes.add_file(
+            "repo/.gitattributes",
+            "# comment\n*.inc linguist-language=PHP\nthird_party/** linguist-vendored\ndocs/** -linguist-documentation\n",
+        );
+        attributes.add_file("repo/gen/.gitattributes", "*.c linguist-generated=true\n");
+
+        let inc = attributes.attributes_for("repo/lib/util.inc");
+        assert_eq!(inc.language.as_deref(), Some("PHP"));
+
+        assert_eq!(
+            attributes
+                .attributes_for("repo/third_party/lib/a.c")
+                .vendored,
+            Some(true)
+        );
+        assert_eq!(
+            attributes
+                .attributes_for("repo/docs/guide.md")
+                .documentation,
+            Some(false)
+        );
+        assert!(attributes.attributes_for("repo/gen/out.c").is_excluded());
+        assert!(!attributes.attributes_for("repo/src/out.c").is_excluded());
+        assert!(!attributes
+            .attributes_for("other/third_party/a.c")
+            .is_excluded());
+    }
+
+    #[test]
+    fn test_gitattributes_precedence() {
+        let mut attributes = GitAttributes::new();
+        attributes.add_file("repo/sub/.gitattributes", "*.js -linguist-vendored\n");
+        attributes.add_file(
+            "repo/.gitattributes",
+            "*.js linguist-vendored\n*.js linguist-language=TypeScript\n*.js !linguist-language\n",
+        );
+
+        let nested = attributes.attributes_for("repo/sub/app.js");
+        assert_eq!(nested.vendored, Some(false));
+        assert_eq!(nested.language, None);
+
+        assert_eq!(
+            attributes.attributes_for("repo/app.js").vendored,
+            Some(true)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tarball_honors_gitattributes() -> Result<()> {
+        let project = analyze_tarball(&[
+            (
+                "repo-main/.gitattributes",
+                "*.inc linguist-language=PHP\nvendor/** -linguist-vendored\nsrc/gen.rs linguist-generated\n",
+            ),
+            ("repo-main/src/main.rs", "fn main() {}\n"),
+            ("repo-main/src/gen.rs", "fn generated() {}\n"),
+            ("repo-main/lib/helpers.inc", "<?php echo 1;\n"),
+            ("repo-main/vendor/lib.rs", "pub fn vendored() {}\n"),
+            ("repo-main/node_modules/x.js", "var x = 1;\n"),
+        ])
+        .await?;
+
+        let rust = &project.language_analyses["Rust"];
+        let rust_files: Vec<&str> = rust
+            .file_metrics
+            .iter()
+            .map(|f| f.file_path.as_str())
+            .collect();
+        assert_eq!(rust_files, vec!["src/main.rs", "vendor/lib.rs"]);
+
+        assert_eq!(project.language_analyses["PHP"].file_metrics.len(), 1);
+        assert!(!project.language_analyses.contains_key("JavaScript"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_ignore_rules_semantics() {
+        let mut rules = IgnoreRules::new();
+        rules.add_file(
+            "repo/.bradarignore",
+            "# generated output\n*.gen.ts\n!keep.gen.ts\n/dist\nbuild/\ndocs/**/*.md\n\\#literal\n",
+        );
+
+        assert!(rules.is_ignored("repo/src/api.gen.ts"));
+        assert!(!rules.is_ignored("repo/src/keep.gen.ts"));
+        assert!(rules.is_ignored("repo/dist/app.js"));
+        assert!(!rules.is_ignored("repo/src/dist/app.js"));
+        assert!(rules.is_ignored("repo/src/build/out.js"));
+        assert!(!rules.is_ignored("repo/src/build"));
+        assert!(rules.is_ignored("repo/docs/a/b/guide.md"));
+        assert!(rules.is_ignored("repo/docs/guide.md"));
+        assert!(rules.is_ignored("repo/#literal"));
+        assert!(!rules.is_ignored("repo/src/main.rs"));
+        assert!(!rules.is_ignored("other/src/api.gen.ts"));
+    }
+
+    #[test]
+    fn test_ignore_rules_cannot_reinclude_in_ignored_directory() {
+        let mut rules = IgnoreRules::new();
+        rules.add_file(".gitignore", "generated/\n!generated/keep.rs\n");
+        rules.add_file("src/.gitignore", "*.rs\n!lib.rs\n");
+
+        assert!(rules.is_ignored("generated/keep.rs"));
+        assert!(rules.is_ignored("src/main.rs"));
+        assert!(!rules.is_ignored("src/lib.rs"));
+        assert!(!rules.is_ignored("main.rs"));
+    }
+
+    #[tokio::test]
+    async fn test_tarball_honors_bradarignore() -> Result<()> {
+        let files = [
+            ("repo-main/.bradarignore", "generated/\n"),
+            ("repo-main/src/.gitignore", "*.tmp.rs\n"),
+            ("repo-main/src/main.rs", "fn main() {}\n"),
+            ("repo-main/src/scratch.tmp.rs", "fn scratch() {}\n"),
+            ("repo-main/generated/api.rs", "fn api() {}\n"),
+            ("repo-main/nested/.bradarignore", "*.rs\n"),
+            ("repo-main/nested/lib.rs", "fn lib() {}\n"),
+        ];
+
+        let project = analyze_tarball(&files).await?;
+        assert_eq!(
+            analyzed_paths(&project),
+            vec!["nested/lib.rs", "src/main.rs", "src/scratch.tmp.rs",]
+        );
+
+        let filter = IntelligentFilter {
+            respect_gitignore: true,
+            ..IntelligentFilter::default()
+        };
+        let project = analyze_tarball_with(&files, &filter).await?;
+        assert_eq!(
+            analyzed_paths(&project),
+            vec!["nested/lib.rs", "src/main.rs"]
+        );
+
+        let filter = IntelligentFilter {
+            respect_bradarignore: false,
+            ..IntelligentFilter::default()
+        };
+        let project = analyze_tarball_with(&files, &filter).await?;
+        assert_eq!(analyzed_paths(&project).len(), 4);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match(
+            "src/**/generated/*.rs",
+            "src/a/b/generated/api.rs"
+        ));
+        assert!(glob_match("src/**/generated/*.rs", "src/generated/api.rs"));
+        assert!(!glob_match(
+            "src/**/generated/*.rs",
+            "src/generated/nested/api.rs"
+        ));
+        assert!(glob_match("*.{pb,g}.go", "api.pb.go"));
+        assert!(glob_match("*.{pb,g}.go", "parser.g.go"));
+        assert!(!glob_match("*.{pb,g}.go", "main.go"));
+        assert!(glob_match("test_?.py", "test_1.py"));
+        assert!(!glob_match("test_?.py", "test_10.py"));
+        assert!(glob_match("[a-c]*.[!o]", "bar.c"));
+        assert!(!glob_match("[a-c]*.[!o]", "bar.o"));
+        assert!(!glob_match("[a-c]*", "dar"));
+        assert!(glob_match("{src,lib}/{a,b{1,2}}.rs", "lib/b2.rs"));
+        assert!(glob_match("\\{literal\\}", "{literal}"));
+        assert!(!glob_match("*.rs", "src/main.rs"));
+        assert!(glob_match("**/*.rs", "src/main.rs"));
+        assert!(glob_match("docs/**", "docs/a/b.md"));
+    }
+
+    #[test]
+    fn test_path_match_anchoring() {
+        assert!(path_match("*.lock", "Cargo.lock"));
+        assert!(path_match("*.lock", "crates/core/Cargo.lock"));
+        assert!(path_match("src/**/generated/*.rs", "src/x/generated/a.rs"));
+        assert!(path_match("/src/*.rs", "src/main.rs"));
+        assert!(!path_match("/src/*.rs", "pkg/src/x.rs"));
+        assert!(path_match("src/*.rs", "src/main.rs"));
+        assert!(!path_match("src/*.rs", "pkg/src/x.rs"));
+        assert!(path_match("**/src/*.rs", "pkg/src/x.rs"));
+        assert!(path_match("target/", "target/debug/main.rs"));
+        assert!(path_match("target/", "crates/a/target/out.rs"));
+        assert!(!path_match("target/", "target"));
+        assert!(path_match("/gen/", "gen/api.rs"));
+        assert!(!path_match("/gen/", "src/gen/api.rs"));
+    }
+
+    #[test]
+    fn test_filter_include_and_exclude_patterns() {
+        let filter = IntelligentFilter {
+            custom_ignore_patterns: vec!["*.{pb,g}.go".to_string(), "test_?.py".to_string()],
+            include_patterns: vec!["**/*.go".to_string(), "**/*.py".to_string()],
+            ..IntelligentFilter::default()
+        };
+
+        assert!(filter.should_process_file("repo/cmd/main.go", 100));
+        assert!(filter.should_process_file("repo/tool.py", 100));
+        assert!(!filter.should_process_file("repo/api/api.pb.go", 100));
+        assert!(!filter.should_process_file("repo/test_1.py", 100));
+        assert!(!filter.should_process_file("repo/src/lib.rs", 100));
+
+        let filter = IntelligentFilter::default();
+        assert!(filter.should_process_file("repo/src/lib.rs", 100));
+    }
+
+    #[test]
+    fn test_filter_decisions() {
+        let filter = IntelligentFilter {
+            ignore_test_dirs: true,
+            ignore_docs_dirs: true,
+            custom_ignore_patterns: vec!["*.lock".to_string()],
+            ..IntelligentFilter::default()
+        };
+        let mut ignore_rules = IgnoreRules::new();
+        ignore_rules.add_file("repo/.bradarignore", "gen/\n");
+        let none = LinguistAttributes::default();
+
+        let cases = [
+            ("repo/src/main.rs", 10, FilterDecision::Include),
+            (
+                "repo/src/big.rs",
+                10 * 1024 * 1024,
+                FilterDecision::TooLarge,
+            ),
+            ("repo/.env", 10, FilterDecision::Hidden),
+            ("repo/logo.png", 10, FilterDecision::Binary),
+            ("repo/node_modules/logo.png", 10, FilterDecision::Binary),
+            (
+                "repo/assets/photo.jpg",
+                10 * 1024 * 1024,
+                FilterDecision::Binary,
+            ),
+            (
+                "repo/api/service.pb.go",
+                10,
+                FilterDecision::Generated("*.pb.go".to_string()),
+            ),
+            (
+                "repo/gen-py/svc/ttypes.py",
+                10,
+                FilterDecision::Generated("gen-*/".to_string()),
+            ),
+            ("repo/target/out.rs", 10, FilterDecision::BuildDir),
+            ("repo/node_modules/x.js", 10, FilterDecision::PackageDir),
+            ("repo/tests/a.rs", 10, FilterDecision::TestDir),
+            ("repo/docs/a.md", 10, FilterDecision::DocsDir),
+            (
+                "repo/gen/a.rs",
+                10,
+                FilterDecision::IgnoreFile("gen".to_string()),
+            ),
+            (
+                "repo/Cargo.lock",
+                10,
+                FilterDecision::CustomPattern("*.lock".to_string()),
+            ),
+        ];
+        for (path, size, expected) in cases {
+            assert_eq!(
+                filter.decide_with_ignore_rules(path, size, &none, &ignore_rules),
+                expected,
+                "{}",
+                path
+            );
+        }
+
+        let vendored = LinguistAttributes {
+            vendored: Some(true),
+            ..LinguistAttributes::default()
+        };
+        assert_eq!(
+            filter.decide("repo/src/main.rs", 10, &vendored),
+            FilterDecision::LinguistAttribute("linguist-vendored".to_string())
+        );
+
+        let aggressive = IntelligentFilter::aggressive();
+        assert_eq!(
+            aggressive.decide("repo/README", 10, &none),
+            FilterDecision::ExtensionNotAllowed
+        );
+
+        let included = IntelligentFilter {
+            include_patterns: vec!["*.go".to_string()],
+            ..IntelligentFilter::default()
+        };
+        assert_eq!(
+            included.decide("repo/main.rs", 10, &none),
+            FilterDecision::NotIncluded
+        );
+        assert_eq!(
+            FilterDecision::CustomPattern("*.lock".to_string()).to_string(),
+            "custom_pattern (*.lock)"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tarball_explain_filter() -> Result<()> {
+        let files = [
+            ("repo/src/main.rs", "fn main() {}\n"),
+            ("repo/.hidden.rs", "fn hidden() {}\n"),
+            ("repo/empty.rs", ""),
+        ];
+
+        let project = analyze_tarball(&files).await?;
+        assert!(project.filter_decisions.is_empty());
+
+        let filter = IntelligentFilter {
+            explain: true,
+            ..IntelligentFilter::default()
+        };
+        let project = analyze_tarball_with(&files, &filter).await?;
+        let decisions: Vec<(&str, &FilterDecision)> = project
+            .filter_decisions
+            .iter()
+            .map(|entry| (entry.path.as_str(), &entry.decision))
+            .collect();
+        assert_eq!(
+            decisions,
+            vec![
+                ("src/main.rs", &FilterDecision::Include),
+                (".hidden.rs", &FilterDecision::Hidden),
+                ("empty.rs", &FilterDecision::Empty),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_tarball_filter_stats_by_reason() -> Result<()> {
+        let project = analyze_tarball(&[
+            ("repo/src/main.rs", "fn main() {}\n"),
+            ("repo/.hidden.rs", "fn hidden() {}\n"),
+            ("repo/.env.rs", "fn env() {}\n"),
+            ("repo/empty.rs", ""),
+        ])
+        .await?;
+
+        let stats = &project.filter_stats;
+        assert_eq!(stats.total_entries, 4);
+        assert_eq!(stats.processed, 1);
+        assert_eq!(stats.filtered_out, 3);
+        assert_eq!(stats.by_reason["hidden"].entries, 2);
+        assert_eq!(stats.by_reason["hidden"].bytes, 27);
+        assert_eq!(stats.by_reason["empty"].entries, 1);
+        assert!(!stats.by_reason.contains_key("include"));
+
+        let json = serde_json::to_value(&project).unwrap();
+        assert_eq!(json["filter_stats"]["by_reason"]["hidden"]["entries"], 2);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_binary_content_detection() -> Result<()> {
+        // Binary files are recognized ahead of the size and directory rules.
+        let filter = IntelligentFilter {
+            max_file_size: 40,
+            ..IntelligentFilter::default()
+        };
+        let project = analyze_tarball_with(
+            &[
+                ("repo/src/main.rs", "fn main() {\n    run();\n}\n"),
+                ("repo/bin/tool", "\x7fELF\x02\x01\x01\0\0\0\0"),
+                ("repo/data/frames", "\x01\x02\x03\x04\x05\x06\x07\x08"),
+                ("repo/assets/logo.png", "PNG\r\n"),
+                ("repo/assets/blob", &"\0".repeat(64)),
+                ("repo/node_modules/pkg/icon.png", "PNG\r\n"),
+                ("repo/node_modules/pkg/index.js", "module.exports = 1;\n"),
+                ("repo/notes", "caf\u{e9} na\u{ef}ve\n"),
+            ],
+            &filter,
+        )
+        .await?;
+
+        let mut languages: Vec<_> = project.language_analyses.keys().cloned().collect();
+        languages.sort();
+        assert_eq!(languages, vec!["Rust", "Text"]);
+
+        let mut binary: Vec<_> = project
+            .binary_files
+            .iter()
+            .map(|file| file.file_path.as_str())
+            .collect();
+        binary.sort();
+        assert_eq!(
+            binary,
+            vec![
+                "assets/blob",
+                "assets/logo.png",
+                "bin/tool",
+                "data/frames",
+                "node_modules/pkg/icon.png"
+            ]
+        );
+        for file in &project.binary_files {
+            assert_eq!(file.category, FileCategory::Binary);
+            assert_eq!(file.total_lines, 0);
+        }
+
+        let summary = project.get_summary();
+        assert_eq!(summary.total_files, 2);
+        assert_eq!(summary.binary_files, 5);
+        assert_eq!(summary.binary_size_bytes, 64 + 11 + 8 + 5 + 5);
+        assert_eq!(summary.language_count, 2);
+        assert_eq!(summary.primary_language.as_deref(), Some("Rust"));
+        assert_eq!(
+            project.directory_tree.children.keys().collect::<Vec<_>>(),
+            vec!["src"]
+        );
+
+        let stats = &project.filter_stats;
+        assert_eq!(stats.by_reason["binary"].entries, 5);
+        assert_eq!(stats.by_reason["package_dir"].entries, 1);
+        assert!(!stats.by_reason.contains_key("too_large"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_non_utf8_decoding() -> Result<()> {
+        let utf16: Vec<u8> = [0xff, 0xfe]
+            .into_iter()
+            .chain(
+                "// resources\r\nIDI_APP ICON \"app.ico\"\r\n"
+                    .encode_utf16()
+                    .flat_map(u16::to_le_bytes),
+            )
+            .collect();
+        let latin1 = b"/* Auteur: Andr\xe9 */\nint main(void) { return 0; }\n".to_vec();
+        let (shift_jis, _, _) =
+            encoding_rs::SHIFT_JIS.encode("// \u{3053}\u{3093}\u{306b}\u{3061}\u{306f}\nint x;\n");
+        let bom = b"\xef\xbb\xbf// main\nfn main() {}\n".to_vec();
+
+        let tar = build_tar(&[
+            ("repo/app.rc", utf16),
+            ("repo/legacy.c", latin1),
+            ("repo/hello.c", shift_jis.into_owned()),
+            ("repo/main.rs", bom),
+        ]);
+        let mut project = ProjectAnalysis::new("encodings");
+        process_archive_reader(tar.as_slice(), &mut project, &IntelligentFilter::default())?;
+
+        let files: std::collections::HashMap<_, _> = project
+            .language_analyses
+            .values()
+            .flat_map(|analysis| &analysis.file_metrics)
+            .map(|file| (file.file_path.as_str(), file))
+            .collect();
+        assert_eq!(files.len(), 4);
+
+        let expected = [
+            ("app.rc", "UTF-16LE", 2),
+            ("legacy.c", "windows-1252", 2),
+            ("hello.c", "Shift_JIS", 2),
+            ("main.rs", "UTF-8", 2),
+        ];
+        for (path, encoding, lines) in expected {
+            assert_eq!(files[path].encoding.as_deref(), Some(encoding), "{}", path);
+            assert_eq!(files[path].total_lines, lines, "{}", path);
+            assert_ne!(files[path].category, FileCategory::Binary, "{}", path);
+        }
+        assert_eq!(files["legacy.c"].comment_lines, 1);
+        assert_eq!(files["main.rs"].code_lines, 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_generated_file_detection() -> Result<()> {
+        let files = [
+            (
+                "repo/.gitattributes",
+                "schema.pb.go -linguist-generated\nbindings.go linguist-generated\n",
+            ),
+            ("repo/main.go", "package main\n\nfunc main() {}\n"),
+            (
+                "repo/zz_deepcopy.go",
+                "// Code generated by controller-gen. DO NOT EDIT.\n\npackage main\n",
+            ),
+            (
+                "repo/Resources.Designer.cs",
+                "// <auto-generated>\n//   by a tool\n// </auto-generated>\nclass R {}\n",
+            ),
+            ("repo/schema.js", "/**\n * @generated\n */\nexport {};\n"),
+            ("repo/api/service.pb.go", "package api\n"),
+            ("repo/schema.pb.go", "package main\n"),
+            ("repo/notes.go", "package main\n\nvar s = \"@generated\"\n"),
+            ("repo/bindings.go", "package main\n\nfunc bind() {}\n"),
+        ];
+
+        let project = analyze_tarball(&files).await?;
+        assert_eq!(
+            analyzed_paths(&project),
+            vec!["main.go", "notes.go", "schema.pb.go"]
+        );
+        assert_eq!(project.filter_stats.by_reason["generated"].entries, 5);
+        assert_eq!(project.filter_stats.generated_lines, 3 + 4 + 4 + 1 + 3);
+        let summary = project.get_summary();
+        assert_eq!(summary.total_files, 3);
+        assert_eq!(summary.generated_files, 5);
+        assert_eq!(summary.generated_lines, 3 + 4 + 4 + 1 + 3);
+        assert_eq!(summary.handwritten_lines, summary.total_lines);
+        assert_eq!(summary.handwritten_lines, 3 + 3 + 1);
+
+        let filter = IntelligentFilter {
+            count_generated: true,
+            ..IntelligentFilter::default()
+        };
+        let project = analyze_tarball_with(&files, &filter).await?;
+        let mut generated: Vec<_> = project
+            .language_analyses
+            .values()
+            .flat_map(|analysis| &analysis.file_metrics)
+            .filter(|file| file.generated)
+            .map(|file| file.file_path.as_str())
+            .collect();
+        generated.sort();
+        assert_eq!(
+            generated,
+            vec![
+                "Resources.Designer.cs",
+                "api/service.pb.go",
+                "bindings.go",
+                "schema.js",
+                "zz_deepcopy.go"
+            ]
+        );
+
+        let summary = project.get_summary();
+        assert_eq!(summary.total_files, 9 - 1);
+        assert_eq!(summary.generated_files, 5);
+        assert_eq!(summary.generated_lines, 3 + 4 + 4 + 1 + 3);
+        assert_eq!(summary.handwritten_lines, 3 + 3 + 1);
+        assert!(!project.filter_stats.by_reason.contains_key("generated"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_tarball_subpath_scoping() -> Result<()> {
+        let files = [
+            ("mono-main/.gitattributes", "*.pb.rs linguist-generated\n"),
+            (
+                "mono-main/services/billing/src/lib.rs",
+                "pub fn bill() {}\n",
+            ),
+            ("mono-main/services/billing/api.pb.rs", "pub fn api() {}\n"),
+            ("mono-main/services/billing-ui/app.ts", "export {};\n"),
+            ("mono-main/services/auth/src/lib.rs", "pub fn auth() {}\n"),
+            ("mono-main/README.md", "# Mono\n"),
+        ];
+
+        let filter = IntelligentFilter {
+            subpath: Some("services/billing".to_string()),
+            ..IntelligentFilter::default()
+        };
+        let project = analyze_tarball_with(&files, &filter).await?;
+        assert_eq!(
+            analyzed_paths(&project),
+            vec!["services/billing/src/lib.rs"]
+        );
+        assert_eq!(project.filter_stats.total_entries, 2);
+
+        let missing = IntelligentFilter {
+            subpath: Some("services/missing".to_string()),
+            ..IntelligentFilter::default()
+        };
+        assert!(analyze_tarball_with(&files, &missing).await.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_directory_tree_rollup() -> Result<()> {
+        let project = analyze_tarball(&[
+            ("repo/build.rs", "fn main() {}\n"),
+            ("repo/src/main.rs", "fn main() {}\n// entry\n"),
+            ("repo/src/core/lib.rs", "pub fn a() {}\npub fn b() {}\n"),
+            ("repo/src/core/deep/mod.rs", "pub fn c() {}\n"),
+            ("repo/tools/gen.py", "print(1)\n"),
+        ])
+        .await?;
+
+        let tree = &project.directory_tree;
+        assert_eq!(tree.metrics.file_count, 5);
+        assert_eq!(tree.metrics.code_lines, 6);
+
+        let src = &tree.children["src"];
+        assert_eq!(src.path, "src");
+        assert_eq!(src.metrics.file_count, 3);
+        assert_eq!(src.metrics.comment_lines, 1);
+
+        let core = &src.children["core"];
+        assert_eq!(core.path, "src/core");
+        assert_eq!(core.metrics.file_count, 2);
+        // Deeper directories roll up into the last level that is kept.
+        assert!(core.children.is_empty());
+
+        let names: Vec<&str> = tree.sorted_children().iter().map(|d| d.name()).collect();
+        assert_eq!(names, vec!["src", "tools"]);
+
+        let json = serde_json::to_value(&project).unwrap();
+        assert_eq!(
+            json["directory_tree"]["children"]["src"]["children"]["core"]["metrics"]["file_count"],
+            2
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_directory_tree_depth() -> Result<()> {
+        let mut project = ProjectAnalysis::new("depth").with_directory_depth(3);
+        project.add_file_metrics(FileMetrics::new(
+            "repo/src/core/deep/mod.rs",
+            "Rust".to_string(),
+            1,
+            1,
+            0,
+            0,
+        )?)?;
+
+        let core = &project.directory_tree.children["repo"].children["src"].children["core"];
+        assert_eq!(core.path, "repo/src/core");
+        assert!(core.children.is_empty());
+
+        let mut flat = ProjectAnalysis::new("flat").with_directory_depth(0);
+        flat.add_file_metrics(FileMetrics::new(
+            "repo/a.rs",
+            "Rust".to_string(),
+            1,
+            1,
+            0,
+            0,
+        )?)?;
+        assert!(flat.directory_tree.children.is_empty());
+        assert_eq!(flat.directory_tree.metrics.file_count, 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_tarball_strips_archive_root() -> Result<()> {
+        let encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        let mut builder = tar::Builder::new(encoder);
+
+        let comment = b"52 comment=1234567890abcdef1234567890abcdef12345678\n";
+        let mut header = tar::Header::new_ustar();
+        header.set_entry_type(tar::EntryType::XGlobalHeader);
+        header.set_size(comment.len() as u64);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, "pax_global_header", &comment[..])
+            .unwrap();
+
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(tar::EntryType::Directory);
+        header.set_size(0);
+        header.set_mode(0o755);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, "repo-1234567/", std::io::empty())
+            .unwrap();
+
+        for (path, content) in [
+            ("repo-1234567/src/main.rs", "fn main() {}\n"),
+            ("repo-1234567/README.md", "# Repo\n"),
+        ] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, path, content.as_bytes())
+                .unwrap();
+        }
+        let bytes = bytes::Bytes::from(builder.into_inner().unwrap().finish().unwrap());
+
+        let mut project = ProjectAnalysis::new("strip");
+        let filter = IntelligentFilter::default();
+        process_tarball(bytes.clone(), &mut project, &filter, &NoOpProgressHook).await?;
+        assert_eq!(project.archive_root.as_deref(), Some("repo-1234567"));
+        assert_eq!(analyzed_paths(&project), vec!["README.md", "src/main.rs"]);
+
+        let mut project = ProjectAnalysis::new("keep");
+        let filter = IntelligentFilter {
+            keep_archive_root: true,
+            ..IntelligentFilter::default()
+        };
+        process_tarball(bytes, &mut project, &filter, &NoOpProgressHook).await?;
+        assert_eq!(project.archive_root.as_deref(), Some("repo-1234567"));
+        assert_eq!(
+            analyzed_paths(&project),
+            vec!["repo-1234567/README.md", "repo-1234567/src/main.rs"]
+        );
+
+        // Without a single shared directory nothing is stripped.
+        let project = analyze_tarball(&[
+            ("a/main.rs", "fn main() {}\n"),
+            ("b/lib.rs", "pub fn lib() {}\n"),
+        ])
+        .await?;
+        assert_eq!(project.archive_root, None);
+        assert_eq!(analyzed_paths(&project), vec!["a/main.rs", "b/lib.rs"]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_filters_see_root_relative_paths() -> Result<()> {
+        let files = [
+            ("repo-main/.gitattributes", "/vendor/** linguist-vendored\n"),
+            ("repo-main/src/main.rs", "fn main() {}\n"),
+            ("repo-main/lib.rs", "pub fn lib() {}\n"),
+            ("repo-main/pkg/src/x.rs", "pub fn x() {}\n"),
+            ("repo-main/vendor/dep.rs", "pub fn dep() {}\n"),
+        ];
+
+        let filter = IntelligentFilter {
+            custom_ignore_patterns: vec!["/src/*.rs".to_string()],
+            explain: true,
+            ..IntelligentFilter::default()
+        };
+        let project = analyze_tarball_with(&files, &filter).await?;
+        assert_eq!(project.archive_root.as_deref(), Some("repo-main"));
+        assert_eq!(analyzed_paths(&project), vec!["lib.rs", "pkg/src/x.rs"]);
+
+        let decision = |path: &str| {
+            project
+                .filter_decisions
+                .iter()
+                .find(|d| d.path == path)
+                .map(|d| d.decision.clone())
+        };
+        assert_eq!(
+            decision("src/main.rs"),
+            Some(FilterDecision::CustomPattern("/src/*.rs".to_string()))
+        );
+        assert_eq!(
+            decision("vendor/dep.rs"),
+            Some(FilterDecision::LinguistAttribute(
+                "linguist-vendored".to_string()
+            ))
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stream_without_archive_root_starting_with_hidden_directory() -> Result<()> {
+        // `git archive HEAD` without `--prefix`, whose first entry looks like
+        // a wrapper directory until the next one arrives.
+        let mut builder = tar::Builder::new(Vec::new());
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(tar::EntryType::Directory);
+        header.set_size(0);
+        header.set_mode(0o755);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, ".github/", std::io::empty())
+            .unwrap();
+        for (path, content) in [
+            (".github/workflows/ci.yml", "on: push\n"),
+            ("src/main.rs", "fn main() {}\n"),
+        ] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, path, content.as_bytes())
+                .unwrap();
+        }
+        let bytes = builder.into_inner().unwrap();
+
+        let filter = IntelligentFilter {
+            explain: true,
+            ..IntelligentFilter::default()
+        };
+        let mut project = ProjectAnalysis::new("stdin");
+        process_archive_reader(bytes.as_slice(), &mut project, &filter)?;
+        assert_eq!(project.archive_root, None);
+        assert_eq!(analyzed_paths(&project), vec!["src/main.rs"]);
+        assert!(project
+            .filter_decisions
+            .iter()
+            .any(|d| d.path == ".github/workflows/ci.yml" && d.decision == FilterDecision::Hidden));
+
+        Ok(())
+    }
+
+    fn build_zip(files: &[(&str, &str)]) -> bytes::Bytes {
+        use std::io::Write;
+        use zip::write::SimpleFileOptions;
+
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        let options =
+            SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+        writer.add_directory("repo-main/", options).unwrap();
+        for (path, content) in files {
+            writer.start_file(*path, options).unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+
+        bytes::Bytes::from(writer.finish().unwrap().into_inner())
+    }
+
+    #[tokio::test]
+    async fn test_zip_archive_analysis() -> Result<()> {
+        let bytes = build_zip(&[
+            ("repo-main/src/main.rs", "fn main() {\n    // entry\n}\n"),
+            ("repo-main/app.py", "print('hi')\n"),
+            (
+                "repo-main/node_modules/dep/index.js",
+                "module.exports = 1;\n",
+            ),
+        ]);
+        assert_eq!(ArchiveFormat::detect(&bytes), Some(ArchiveFormat::Zip));
+
+        // Buffered archives are read through the central directory.
+        let mut buffered = ProjectAnalysis::new("zip");
+        let filter = IntelligentFilter::default();
+        process_tarball(bytes.clone(), &mut buffered, &filter, &NoOpProgressHook).await?;
+        assert_eq!(buffered.archive_root.as_deref(), Some("repo-main"));
+        assert_eq!(analyzed_paths(&buffered), vec!["app.py", "src/main.rs"]);
+
+        // Streamed archives are read through the local headers.
+        let mut streamed = ProjectAnalysis::new("zip");
+        process_archive_reader(bytes.as_ref(), &mut streamed, &filter)?;
+        assert_eq!(analyzed_paths(&streamed), analyzed_paths(&buffered));
+
+        let rust = &buffered.language_analyses["Rust"];
+        assert_eq!(rust.aggregate_metrics.code_lines, 2);
+        assert_eq!(rust.aggregate_metrics.comment_lines, 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_subpath_without_archive_root() -> Result<()> {
+        use std::io::Write;
+        use zip::write::SimpleFileOptions;
+
+        // Like Azure DevOps `items?path=/&$format=zip` downloads, which have
+        // no wrapper directory.
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        for (path, content) in [
+            ("README.md", "# Repo\n"),
+            ("src/main.rs", "fn main() {}\n"),
+            ("src/util/mod.rs", "pub fn util() {}\n"),
+            ("tools/gen.py", "print(1)\n"),
+        ] {
+            writer
+                .start_file(path, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        let bytes = writer.finish().unwrap().into_inner();
+
+        let filter = IntelligentFilter {
+            subpath: Some("src".to_string()),
+            ..IntelligentFilter::default()
+        };
+
+        let mut buffered = ProjectAnalysis::new("azure");
+        process_tarball(
+            bytes::Bytes::from(bytes.clone()),
+            &mut buffered,
+            &filter,
+            &NoOpProgressHook,
+        )
+        .await?;
+        assert_eq!(buffered.archive_root, None);
+        assert_eq!(
+            analyzed_paths(&buffered),
+            vec!["src/main.rs", "src/util/mod.rs"]
+        );
+
+        let mut streamed = ProjectAnalysis::new("azure");
+        process_archive_reader(bytes.as_slice(), &mut streamed, &filter)?;
+        assert_eq!(analyzed_paths(&streamed), analyzed_paths(&buffered));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unrecognized_archive_format() {
+        assert_eq!(
+            ArchiveFormat::detect(&build_tarball(&[("a.rs", "")])),
+            Some(ArchiveFormat::TarGz)
+        );
+
+        let mut project = ProjectAnalysis::new("bogus");
+        let result = process_tarball(
+            bytes::Bytes::from_static(b"<html>not an archive</html>"),
+            &mut project,
+            &IntelligentFilter::default(),
+            &NoOpProgressHook,
+        )
+        .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_compressed_tarball_formats() -> Result<()> {
+        use std::io::Write;
+
+        let tar = build_tar(&[
+            ("release-1.0/src/lib.rs", "pub fn lib() {}\n"),
+            ("release-1.0/setup.py", "import os\n"),
+        ]);
+
+        let mut bzip2 = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+        bzip2.write_all(&tar).unwrap();
+        let mut xz =
+            lzma_rust2::XzWriter::new(Vec::new(), lzma_rust2::XzOptions::with_preset(6)).unwrap();
+        xz.write_all(&tar).unwrap();
+        let zstd = ruzstd::encoding::compress_to_vec(
+            tar.as_slice(),
+            ruzstd::encoding::CompressionLevel::Fastest,
+        );
+
+        for (format, bytes) in [
+            (ArchiveFormat::TarBz2, bzip2.finish().unwrap()),
+            (ArchiveFormat::TarXz, xz.finish().unwrap()),
+            (ArchiveFormat::TarZst, zstd),
+            (ArchiveFormat::Tar, tar.clone()),
+        ] {
+            assert_eq!(ArchiveFormat::detect(&bytes), Some(format));
+
+            let mut buffered = ProjectAnalysis::new("release");
+            let filter = IntelligentFilter::default();
+            process_tarball(
+                bytes::Bytes::from(bytes.clone()),
+                &mut buffered,
+                &filter,
+                &NoOpProgressHook,
+            )
+            .await?;
+            assert_eq!(buffered.archive_root.as_deref(), Some("release-1.0"));
+            assert_eq!(analyzed_paths(&buffered), vec!["setup.py", "src/lib.rs"]);
+
+            let mut streamed = ProjectAnalysis::new("release");
+            process_archive_reader(bytes.as_slice(), &mut streamed, &filter)?;
+            assert_eq!(analyzed_paths(&streamed), analyzed_paths(&buffered));
+        }
+
+        Ok(())
+    }
+
+    fn scratch_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("bradar-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write_files(root: &std::path::Path, files: &[(&str, &str)]) {
+        for (path, content) in files {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_local_directory_analysis() -> Result<()> {
+        let root = scratch_dir("workspace");
+        write_files(
+            &root,
+            &[
+                (".bradarignore", "generated/\n"),
+                (".git/config", "[core]\n"),
+                ("src/main.rs", "fn main() {}\n"),
+                ("generated/schema.rs", "pub struct Schema;\n"),
+                ("node_modules/dep/index.js", "module.exports = 1;\n"),
+                // Only the `.bradarignore` at the root is read.
+                ("tools/.bradarignore", "*.py\n"),
+                ("tools/gen.py", "print(1)\n"),
+            ],
+        );
+
+        let analyzer = RemoteAnalyzer::new();
+        let project = analyzer.analyze_url(root.to_str().unwrap()).await?;
+        assert!(project.project_name.starts_with("bradar-workspace-"));
+        assert_eq!(project.archive_root, None);
+        assert_eq!(
+            analyzed_paths(&project),
+            vec!["src/main.rs", "tools/gen.py"]
+        );
+        // `.git` is never walked, everything else is accounted for.
+        assert_eq!(project.filter_stats.total_entries, 6);
+
+        // A lone top-level directory is part of the tree, not a wrapper.
+        let single = scratch_dir("single");
+        write_files(&single, &[("src/lib.rs", "pub fn lib() {}\n")]);
+        let project = analyzer.analyze_path(&single).await?;
+        assert_eq!(analyzed_paths(&project), vec!["src/lib.rs"]);
+
+        std::fs::remove_dir_all(&root).unwrap();
+        std::fs::remove_dir_all(&single).unwrap();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_local_archive_analysis() -> Result<()> {
+        let root = scratch_dir("archives");
+        let tarball = root.join("project-1.0.tar.gz");
+        std::fs::write(
+            &tarball,
+            build_tarball(&[("project-1.0/src/main.rs", "fn main() {}\n")]),
+        )
+        .unwrap();
+        let zip = root.join("snapshot.zip");
+        std::fs::write(&zip, build_zip(&[("repo-main/app.py", "print('hi')\n")])).unwrap();
+
+        let analyzer = RemoteAnalyzer::new();
+        let project = analyzer
+            .analyze_url(&format!("file://{}", tarball.display()))
+            .await?;
+        assert_eq!(project.project_name, "project-1.0");
+        assert_eq!(project.archive_root.as_deref(), Some("project-1.0"));
+        assert_eq!(analyzed_paths(&project), vec!["src/main.rs"]);
+
+        let project = analyzer.analyze_path(&zip).await?;
+        assert_eq!(project.project_name, "snapshot");
+        assert_eq!(analyzed_paths(&project), vec!["app.py"]);
+
+        assert!(analyzer
+            .analyze_path(root.join("missing.tar.gz"))
+            .await
+            .is_err());
+
+        std::fs::remove_dir_all(&root).unwrap();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reader_analysis() -> Result<()> {
+        let bytes = build_tarball(&[("app/src/main.rs", "fn main() {}\n")]);
+
+        let analyzer = RemoteAnalyzer::new();
+        let project = analyzer
+            .analyze_reader(std::io::Cursor::new(bytes), "piped")
+            .await?;
+        assert_eq!(project.project_name, "piped");
+        assert_eq!(analyzed_paths(&project), vec!["src/main.rs"]);
+
+        let garbage = std::io::Cursor::new(b"not an archive".to_vec());
+        assert!(analyzer.analyze_reader(garbage, "garbage").await.is_err());
+
+        Ok(())
+    }
+
+    fn git(dir: &std::path::Path, args: &[&str]) {
+        let status = std::process::Command::new("git")
+            .args([
+                "-c",
+                "user.name=Radar",
+                "-c",
+                "user.email=radar@example.com",
+            ])
+            .args(args)
+            .current_dir(dir)
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    #[tokio::test]
+    async fn test_git_repository_analysis() -> Result<()> {
+        let root = scratch_dir("git");
+        // Long enough that repacking stores the second version as a delta.
+        let body: String = (0..200)
+            .map(|i| format!("    let value_{} = {};\n", i, i))
+            .collect();
+        write_files(
+            &root,
+            &[
+                ("src/main.rs", &format!("fn main() {{\n{}}}\n", body)),
+                ("vendor/lib.js", "var x = 1;\n"),
+            ],
+        );
+        git(&root, &["init", "-q", "-b", "main"]);
+        git(&root, &["add", "."]);
+        git(&root, &["commit", "-q", "-m", "first"]);
+        git(&root, &["tag", "-a", "v1", "-m", "release"]);
+
+        write_files(
+            &root,
+            &[
+                (
+                    "src/main.rs",
+                    &format!("fn main() {{\n{}    run();\n}}\n", body),
+                ),
+                ("src/lib.rs", "pub fn run() {}\n"),
+            ],
+        );
+        git(&root, &["add", "."]);
+        git(&root, &["commit", "-q", "-m", "second"]);
+        write_files(&root, &[("src/untracked.rs", "fn untracked() {}\n")]);
+
+        let analyzer = RemoteAnalyzer::new();
+        for packed in [false, true] {
+            if packed {
+                git(&root, &["repack", "-q", "-a", "-d", "-f"]);
+                git(&root, &["prune-packed"]);
+            }
+
+            let head = analyzer.analyze_git(&root, "HEAD").await?;
+            assert_eq!(analyzed_paths(&head), vec!["src/lib.rs", "src/main.rs"]);
+            assert_eq!(head.global_metrics.code_lines, 204);
+
+            let tagged = analyzer.analyze_git(root.join(".git"), "v1").await?;
+            assert!(tagged.project_name.ends_with("@v1"));
+            assert_eq!(analyzed_paths(&tagged), vec!["src/main.rs"]);
+            assert_eq!(tagged.global_metrics.code_lines, 202);
+
+            let parent = analyzer.analyze_git(&root, "main~1").await?;
+            assert_eq!(analyzed_paths(&parent), analyzed_paths(&tagged));
+        }
+
+        assert!(analyzer.analyze_git(&root, "missing").await.is_err());
+        assert!(analyzer.analyze_git(&root, "HEAD~5").await.is_err());
+        for rev in ["HEAD~\u{e9}", "HEAD^\u{e9}", "HEAD~1x", "main^^!"] {
+            let error = analyzer.analyze_git(&root, rev).await.unwrap_err();
+            assert!(error.to_string().contains("Invalid revision"), "{}", rev);
+        }
+
+        std::fs::remove_dir_all(&root).unwrap();
+        Ok(())
+    }
 }